@@ -1,107 +1,629 @@
+use std::path::PathBuf;
 use std::process::Child;
+use std::sync::Arc;
 
 #[cfg(feature = "osd")]
 use std::process::Command;
 
-use crate::audio::AudioRecorder;
-use crate::config::Config;
-use crate::error::{Result, WhsprError};
+use whspr_rs::audio::AudioRecorder;
+use whspr_rs::config::{self, Config};
+use whspr_rs::error::{Result, WhsprError};
+use whspr_rs::file_audio;
+use whspr_rs::inject::TextInjector;
+#[cfg(feature = "osd")]
+use whspr_rs::osd_config::OSD_CONFIG_ENV;
+use whspr_rs::osd_config::OsdConfig;
+use whspr_rs::runtime;
+use whspr_rs::transcribe::{TranscriptionBackend, WhisperLocal};
+
 use crate::feedback::FeedbackPlayer;
-use crate::inject::TextInjector;
-use crate::transcribe::{TranscriptionBackend, WhisperLocal};
+use crate::focus::FocusGuard;
+use crate::postprocess;
+use crate::replace;
+use crate::status_socket::{self, StatusSocket};
+
+/// Phase of the dictation cycle, published to the state file for `whspr-rs status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppState {
+    Recording,
+    Transcribing,
+    Injecting,
+}
+
+impl AppState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AppState::Recording => "recording",
+            AppState::Transcribing => "transcribing",
+            AppState::Injecting => "injecting",
+        }
+    }
+}
+
+pub fn state_file_path() -> PathBuf {
+    runtime::runtime_file("whspr-rs.state")
+}
+
+/// Publishes the current `AppState` to a runtime file, removed on drop, and
+/// to the status socket when enabled.
+struct StateFile {
+    path: Option<PathBuf>,
+    socket: Option<StatusSocket>,
+}
+
+impl StateFile {
+    fn new(path: Option<PathBuf>, socket: Option<StatusSocket>) -> Self {
+        Self { path, socket }
+    }
+
+    fn set(&self, state: AppState) {
+        tracing::debug!("state: {}", state.as_str());
+        if let Some(path) = &self.path
+            && let Err(e) = std::fs::write(path, state.as_str())
+        {
+            tracing::warn!("failed to write state file {}: {e}", path.display());
+        }
+        if let Some(socket) = &self.socket {
+            socket.publish(state.as_str());
+        }
+    }
+}
+
+impl Drop for StateFile {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// How long a shutdown request waits for an in-flight transcription and
+/// injection before giving up on it.
+const SHUTDOWN_GRACE: std::time::Duration = std::time::Duration::from_secs(30);
 
-pub async fn run(config: Config) -> Result<()> {
+/// What the dictation loop reacts to: the process signals, or a script of
+/// them in tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Event {
+    /// SIGUSR1, sent by pressing the key again.
+    Toggle,
+    /// SIGUSR2, which stops a recording but never starts one.
+    Stop,
+    /// Ctrl-C, which is also how `whspr-rs cancel` reaches us.
+    Cancel,
+    /// SIGTERM, from `whspr-rs kill`.
+    Terminate,
+}
+
+trait Events {
+    /// Wait for the next event. Must be cancel-safe: the loop drops the
+    /// future whenever something else happens first.
+    async fn next(&mut self) -> Event;
+}
+
+struct Signals {
+    sigusr1: tokio::signal::unix::Signal,
+    sigusr2: tokio::signal::unix::Signal,
+    sigterm: tokio::signal::unix::Signal,
+}
+
+impl Signals {
+    fn register() -> Result<Self> {
+        use tokio::signal::unix::{SignalKind, signal};
+        Ok(Self {
+            sigusr1: signal(SignalKind::user_defined1())?,
+            sigusr2: signal(SignalKind::user_defined2())?,
+            sigterm: signal(SignalKind::terminate())?,
+        })
+    }
+}
+
+impl Events for Signals {
+    async fn next(&mut self) -> Event {
+        tokio::select! {
+            _ = self.sigusr1.recv() => Event::Toggle,
+            _ = self.sigusr2.recv() => Event::Stop,
+            _ = tokio::signal::ctrl_c() => Event::Cancel,
+            _ = self.sigterm.recv() => Event::Terminate,
+        }
+    }
+}
+
+/// Record, transcribe and inject one dictation, or several in a row when
+/// `[inject].cooldown_ms` queues them. With `dry_run` the transcript
+/// is also printed to stdout; the caller is expected to have switched
+/// injection to clipboard-only. A `standalone` run, next to an instance that
+/// owns the pid lock, leaves the state file and status socket to that one.
+pub async fn run(config: Config, dry_run: bool, standalone: bool) -> Result<()> {
     // Register signals before startup work to minimize early-signal races.
-    let mut sigusr1 =
-        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined1())?;
-    let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
-
-    let feedback = FeedbackPlayer::new(
-        config.feedback.enabled,
-        &config.feedback.start_sound,
-        &config.feedback.stop_sound,
-    );
-
-    // Play start sound first (blocking), then start recording so the sound
-    // doesn't leak into the mic.
-    feedback.play_start();
-    let mut recorder = AudioRecorder::new(&config.audio);
-    recorder.start()?;
-    let mut osd = spawn_osd();
-    tracing::info!("recording... (run whspr-rs again to stop)");
-
-    // Preload whisper model in background while recording
-    let whisper_config = config.whisper.clone();
-    let model_path = config.resolved_model_path();
-    let model_handle =
-        tokio::task::spawn_blocking(move || WhisperLocal::new(&whisper_config, &model_path));
-
-    tokio::select! {
-        _ = sigusr1.recv() => {
-            tracing::info!("toggle signal received, stopping recording");
-        }
-        _ = tokio::signal::ctrl_c() => {
-            tracing::info!("interrupted, cancelling");
-            kill_osd(&mut osd);
-            recorder.stop()?;
+    let mut signals = Signals::register()?;
+
+    let feedback = FeedbackPlayer::new(&config.feedback);
+
+    // Build the injector up front so config mistakes fail before recording.
+    let injector = TextInjector::new(&config.inject)?;
+    let focus_guard = FocusGuard::new(&config.inject);
+    let replacements = replace::compile_rules(&config.replacements)?;
+    let socket = if config.status.socket && !standalone {
+        match StatusSocket::bind(&status_socket::socket_path()) {
+            Ok(socket) => Some(socket),
+            Err(e) => {
+                tracing::warn!("failed to open status socket: {e}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let state = StateFile::new((!standalone).then(state_file_path), socket);
+    let pipeline = Dictation {
+        config: &config,
+        feedback: &feedback,
+        state: &state,
+        focus_guard: &focus_guard,
+        replacements: &replacements,
+        injector: &injector,
+        dry_run,
+    };
+
+    // Load the whisper model in the background while the first recording runs.
+    // Dictations queued by the cooldown reuse it.
+    let mut model = ModelLoad::start(&config);
+
+    run_dictations(&pipeline, &mut model, &mut signals, || {
+        AudioRecorder::new(&config.audio)
+    })
+    .await
+}
+
+/// The dictation loop of `run`, driven by `events`, with a fresh recorder
+/// from `new_recorder` for every dictation.
+async fn run_dictations<I: Inject>(
+    pipeline: &Dictation<'_, I>,
+    model: &mut ModelLoad,
+    events: &mut impl Events,
+    new_recorder: impl Fn() -> AudioRecorder,
+) -> Result<()> {
+    let Dictation {
+        config,
+        feedback,
+        state,
+        ..
+    } = pipeline;
+    let cooldown = std::time::Duration::from_millis(config.inject.cooldown_ms);
+    loop {
+        // Play start sound first (blocking), then start recording so the sound
+        // doesn't leak into the mic.
+        feedback.play_start();
+        let mut recorder = new_recorder();
+        recorder.start()?;
+        state.set(AppState::Recording);
+        let mut osd = spawn_osd(&config.osd);
+        tracing::info!("recording... (run whspr-rs again to stop)");
+
+        let max_duration = config.audio.max_duration_secs;
+        let max_duration_reached = async {
+            if max_duration > 0 {
+                tokio::time::sleep(std::time::Duration::from_secs(max_duration)).await;
+            } else {
+                std::future::pending::<()>().await;
+            }
+        };
+        tokio::pin!(max_duration_reached);
+
+        let mut partials = PartialTranscripts::new(config);
+
+        loop {
+            tokio::select! {
+                event = events.next() => match event {
+                    Event::Toggle => {
+                        tracing::info!("toggle signal received, stopping recording");
+                        break;
+                    }
+                    Event::Stop => {
+                        tracing::info!("stop signal received, stopping recording");
+                        break;
+                    }
+                    Event::Cancel => {
+                        tracing::info!("interrupted, discarding the recording");
+                        kill_osd(&mut osd);
+                        recorder.stop()?;
+                        feedback.play_cancel();
+                        return Ok(());
+                    }
+                    Event::Terminate => {
+                        tracing::info!("terminated, cancelling");
+                        kill_osd(&mut osd);
+                        recorder.stop()?;
+                        return Ok(());
+                    }
+                },
+                _ = &mut max_duration_reached => {
+                    tracing::warn!("maximum recording duration ({max_duration}s) reached, stopping recording");
+                    break;
+                }
+                _ = partials.next(model, &recorder), if partials.enabled() => {}
+            }
+        }
+
+        // Stop recording before playing feedback so the stop sound doesn't
+        // leak into the mic.
+        kill_osd(&mut osd);
+        let audio = recorder.stop()?;
+        feedback.play_stop();
+        let sample_rate = config.audio.sample_rate;
+
+        if is_too_short(audio.len(), sample_rate, config.audio.min_duration_ms) {
+            tracing::info!(
+                "recording shorter than {}ms, discarding it",
+                config.audio.min_duration_ms
+            );
+            return Ok(());
+        }
+
+        if !config.audio.debug_audio_dir.is_empty() {
+            save_debug_audio(&config.audio.debug_audio_dir, &audio, sample_rate);
+        }
+
+        // From here on the dictation is worth finishing: a shutdown request waits
+        // for it (within limits) instead of dropping the transcript.
+        let dictation = pipeline.finish(model, audio, sample_rate);
+        tokio::pin!(dictation);
+
+        // With a cooldown, pressing the key while this dictation is still being
+        // handled queues another one instead of being ignored.
+        let mut queued = false;
+        let result = loop {
+            tokio::select! {
+                result = &mut dictation => break result,
+                event = events.next() => match event {
+                    Event::Toggle if !cooldown.is_zero() => {
+                        tracing::info!("toggle signal received, recording again after this dictation");
+                        queued = true;
+                    }
+                    Event::Toggle | Event::Stop => {}
+                    Event::Cancel | Event::Terminate => {
+                        tracing::info!(
+                            "shutdown requested, finishing the current dictation (up to {}s)",
+                            SHUTDOWN_GRACE.as_secs()
+                        );
+                        return tokio::time::timeout(SHUTDOWN_GRACE, dictation)
+                            .await
+                            .unwrap_or_else(|_| {
+                                Err(WhsprError::Transcription(
+                                    "dictation did not finish before shutdown".into(),
+                                ))
+                            });
+                    }
+                },
+            }
+        };
+        result?;
+        if cooldown.is_zero() {
+            return Ok(());
+        }
+
+        // Let the target window finish handling the paste before the next
+        // dictation can start.
+        let cooldown_over = tokio::time::sleep(cooldown);
+        tokio::pin!(cooldown_over);
+        loop {
+            tokio::select! {
+                _ = &mut cooldown_over => break,
+                event = events.next() => match event {
+                    Event::Toggle => {
+                        tracing::info!("toggle signal received, recording again after the cooldown");
+                        queued = true;
+                    }
+                    Event::Stop => {}
+                    Event::Cancel | Event::Terminate => return Ok(()),
+                },
+            }
+        }
+        if !queued {
+            return Ok(());
+        }
+    }
+}
+
+/// Where a finished dictation is sent: the `TextInjector`, or a stand-in
+/// that records it in tests.
+trait Inject {
+    async fn inject(&self, text: &str) -> Result<()>;
+}
+
+impl Inject for TextInjector {
+    async fn inject(&self, text: &str) -> Result<()> {
+        TextInjector::inject(self, text).await
+    }
+}
+
+/// What turns a stopped recording into injected text, borrowed from `run`.
+struct Dictation<'a, I> {
+    config: &'a Config,
+    feedback: &'a FeedbackPlayer,
+    state: &'a StateFile,
+    focus_guard: &'a FocusGuard,
+    replacements: &'a [replace::Rule],
+    injector: &'a I,
+    dry_run: bool,
+}
+
+impl<I: Inject> Dictation<'_, I> {
+    /// Transcribe `audio`, clean the text up and inject it.
+    async fn finish(&self, model: &mut ModelLoad, audio: Vec<f32>, sample_rate: u32) -> Result<()> {
+        let Self {
+            config,
+            feedback,
+            state,
+            ..
+        } = self;
+        tracing::info!("transcribing {} samples...", audio.len());
+        state.set(AppState::Transcribing);
+
+        let text = match transcribe_recording(model, audio, sample_rate).await {
+            Ok(text) => text,
+            Err(e) => {
+                feedback.play_error();
+                return Err(e);
+            }
+        };
+
+        if postprocess::is_blocked(&text, &config.postprocess) {
+            tracing::info!("dropping {text:?}, it matches [postprocess].blocklist");
+            return Ok(());
+        }
+        if text.is_empty() {
+            tracing::warn!("transcription returned empty text");
+            feedback.play_error();
+            // When the RMS/duration gates skip transcription, the process would
+            // exit almost immediately after play_error().  PipeWire may still be
+            // draining the sound's last buffer; exiting while it's "warm"
+            // causes an audible click as the OS closes our audio file descriptors.
+            // With speech, transcription takes seconds — providing natural drain time.
+            std::thread::sleep(std::time::Duration::from_millis(150));
             return Ok(());
         }
-        _ = sigterm.recv() => {
-            tracing::info!("terminated, cancelling");
-            kill_osd(&mut osd);
-            recorder.stop()?;
+
+        // Inject text
+        let text = replace::apply_replacements(&text, self.replacements);
+        let text = postprocess::apply(&text, &config.postprocess);
+        if let Some(app) = self.focus_guard.blocked_app() {
+            tracing::warn!("not injecting into {app}, it is in [inject].deny_apps");
+            feedback.play_error();
             return Ok(());
         }
+        feedback.play_complete();
+        show_result_osd(&text, &config.osd);
+        state.set(AppState::Injecting);
+        if self.dry_run {
+            tracing::info!("dry run, copying to clipboard only: {text:?}");
+            println!("{text}");
+        } else {
+            tracing::info!("injecting: {text:?}");
+        }
+        if let Err(e) = self.injector.inject(&text).await {
+            feedback.play_error();
+            return Err(e);
+        }
+
+        tracing::info!("done");
+        Ok(())
     }
+}
 
-    // Stop recording before playing feedback so the stop sound doesn't
-    // leak into the mic.
-    kill_osd(&mut osd);
-    let audio = recorder.stop()?;
-    feedback.play_stop();
-    let sample_rate = config.audio.sample_rate;
+/// Whether a recording of `samples` is below `[audio].min_duration_ms`.
+fn is_too_short(samples: usize, sample_rate: u32, min_duration_ms: u64) -> bool {
+    (samples as u64).saturating_mul(1000) < min_duration_ms.saturating_mul(sample_rate as u64)
+}
 
-    tracing::info!("transcribing {} samples...", audio.len());
+/// Keep a copy of the recording for `[audio].debug_audio_dir`. Failures are
+/// only logged; they shouldn't cost the dictation.
+fn save_debug_audio(dir: &str, audio: &[f32], sample_rate: u32) {
+    let dir = PathBuf::from(config::expand_tilde(dir));
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let path = dir.join(format!("whspr-rs-{millis}.wav"));
+    let result = std::fs::create_dir_all(&dir)
+        .map_err(|e| WhsprError::Audio(format!("failed to create {}: {e}", dir.display())))
+        .and_then(|()| file_audio::write_wav(&path, audio, sample_rate));
+    match result {
+        Ok(()) => tracing::info!("saved recording to {}", path.display()),
+        Err(e) => tracing::warn!("{e}"),
+    }
+}
 
+async fn transcribe_recording(
+    model: &mut ModelLoad,
+    audio: Vec<f32>,
+    sample_rate: u32,
+) -> Result<String> {
     // Await preloaded model (instant if it finished during recording)
-    let backend = model_handle
+    let backend = model.get().await?;
+
+    tokio::task::spawn_blocking(move || backend.transcribe(&audio, sample_rate))
         .await
-        .map_err(|e| WhsprError::Transcription(format!("model loading task failed: {e}")))??;
+        .map_err(|e| WhsprError::Transcription(format!("task panicked: {e}")))?
+}
+
+/// The whisper model, loaded in the background while the first recording
+/// runs and kept for the rest of the run. Shared so partial transcripts can
+/// use it before the recording stops.
+enum ModelLoad {
+    Pending(tokio::task::JoinHandle<Result<WhisperLocal>>),
+    Done(Result<Arc<dyn TranscriptionBackend>>),
+}
+
+impl ModelLoad {
+    fn start(config: &Config) -> Self {
+        let whisper_config = config.whisper.clone();
+        let model_path = config.resolved_model_path();
+        Self::Pending(tokio::task::spawn_blocking(move || {
+            WhisperLocal::new(&whisper_config, &model_path)
+        }))
+    }
+
+    /// The model if it has finished loading, without waiting for it. A load
+    /// error is kept for `into_model` to report.
+    async fn ready(&mut self) -> Option<Arc<dyn TranscriptionBackend>> {
+        if let Self::Pending(handle) = self
+            && handle.is_finished()
+        {
+            *self = Self::Done(join_model(handle).await);
+        }
+        match self {
+            Self::Done(Ok(model)) => Some(Arc::clone(model)),
+            _ => None,
+        }
+    }
 
-    let text = tokio::task::spawn_blocking(move || backend.transcribe(&audio, sample_rate))
+    /// Wait for the model to finish loading. A load error is returned once;
+    /// it ends the run, so there is no later dictation to report it to.
+    async fn get(&mut self) -> Result<Arc<dyn TranscriptionBackend>> {
+        if let Self::Pending(handle) = self {
+            *self = Self::Done(join_model(handle).await);
+        }
+        if let Self::Done(Ok(model)) = self {
+            return Ok(Arc::clone(model));
+        }
+        let failed = Self::Done(Err(WhsprError::Transcription(
+            "the whisper model failed to load".into(),
+        )));
+        match std::mem::replace(self, failed) {
+            Self::Done(result) => result,
+            Self::Pending(_) => unreachable!("the load was joined above"),
+        }
+    }
+}
+
+async fn join_model(
+    handle: &mut tokio::task::JoinHandle<Result<WhisperLocal>>,
+) -> Result<Arc<dyn TranscriptionBackend>> {
+    let model = handle
         .await
-        .map_err(|e| WhsprError::Transcription(format!("task panicked: {e}")))??;
+        .map_err(|e| WhsprError::Transcription(format!("model loading task failed: {e}")))??;
+    Ok(Arc::new(model))
+}
+
+/// Periodically transcribes the most recent audio while recording and prints
+/// the text, when `[whisper].partial_interval_secs` is set.
+struct PartialTranscripts {
+    interval: Option<tokio::time::Interval>,
+    window_samples: usize,
+    sample_rate: u32,
+    last: String,
+}
 
-    if text.is_empty() {
-        tracing::warn!("transcription returned empty text");
-        // When the RMS/duration gates skip transcription, the process would
-        // exit almost immediately after play_stop().  PipeWire may still be
-        // draining the stop sound's last buffer; exiting while it's "warm"
-        // causes an audible click as the OS closes our audio file descriptors.
-        // With speech, transcription takes seconds — providing natural drain time.
-        std::thread::sleep(std::time::Duration::from_millis(150));
-        return Ok(());
+impl PartialTranscripts {
+    fn new(config: &Config) -> Self {
+        let secs = config.whisper.partial_interval_secs;
+        let interval = (secs > 0.0).then(|| {
+            let period = std::time::Duration::from_secs_f64(secs);
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            // A pass can take longer than the period; don't queue up catch-up passes.
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            interval
+        });
+        Self {
+            interval,
+            window_samples: (config.whisper.chunk_secs * config.audio.sample_rate as f64) as usize,
+            sample_rate: config.audio.sample_rate,
+            last: String::new(),
+        }
     }
 
-    // Inject text
-    tracing::info!("injecting: {text:?}");
-    let injector = TextInjector::new();
-    injector.inject(&text).await?;
+    fn enabled(&self) -> bool {
+        self.interval.is_some()
+    }
 
-    tracing::info!("done");
-    Ok(())
+    /// Wait for the next tick, then transcribe the latest window and print it
+    /// if it changed. Ticks before the model has loaded are skipped. A pass
+    /// still running when recording stops finishes in the background.
+    async fn next(&mut self, model: &mut ModelLoad, recorder: &AudioRecorder) {
+        let Some(interval) = self.interval.as_mut() else {
+            return std::future::pending().await;
+        };
+        interval.tick().await;
+        let Some(backend) = model.ready().await else {
+            return;
+        };
+        let audio = recorder.tail(self.window_samples);
+        let sample_rate = self.sample_rate;
+        let text = match tokio::task::spawn_blocking(move || {
+            backend.transcribe(&audio, sample_rate)
+        })
+        .await
+        {
+            Ok(Ok(text)) => text,
+            Ok(Err(e)) => {
+                tracing::debug!("partial transcription failed: {e}");
+                return;
+            }
+            Err(e) => {
+                tracing::debug!("partial transcription task failed: {e}");
+                return;
+            }
+        };
+        if !text.is_empty() && text != self.last {
+            // stderr, so stdout stays the final transcript (e.g. --dry-run).
+            eprintln!("partial: {text}");
+            self.last = text;
+        }
+    }
 }
 
 #[cfg(feature = "osd")]
-fn spawn_osd() -> Option<Child> {
+fn osd_path() -> PathBuf {
     // Look for whspr-osd next to our own binary first, then fall back to PATH
-    let osd_path = std::env::current_exe()
+    std::env::current_exe()
         .ok()
         .and_then(|p| p.parent().map(|dir| dir.join("whspr-osd")))
         .filter(|p| p.exists())
-        .unwrap_or_else(|| "whspr-osd".into());
+        .unwrap_or_else(|| "whspr-osd".into())
+}
+
+/// Command for whspr-osd with the `[osd]` settings passed through the
+/// environment.
+#[cfg(feature = "osd")]
+fn osd_command(osd: &OsdConfig) -> Command {
+    let mut command = Command::new(osd_path());
+    match toml::to_string(osd) {
+        Ok(encoded) => {
+            command.env(OSD_CONFIG_ENV, encoded);
+        }
+        Err(e) => tracing::warn!("failed to encode [osd] config, using defaults: {e}"),
+    }
+    command
+}
+
+/// Written by whspr-osd while its recording overlay is up.
+#[cfg(feature = "osd")]
+fn osd_pid_file_path() -> PathBuf {
+    runtime::runtime_file("whspr-osd.pid")
+}
+
+#[cfg(feature = "osd")]
+fn osd_already_running(pid_file: &std::path::Path) -> bool {
+    std::fs::read_to_string(pid_file)
+        .ok()
+        .and_then(|s| s.trim().parse::<libc::pid_t>().ok())
+        .is_some_and(crate::process_exists)
+}
 
-    match Command::new(&osd_path).spawn() {
+#[cfg(feature = "osd")]
+fn spawn_osd(osd: &OsdConfig) -> Option<Child> {
+    if !osd.autostart {
+        return None;
+    }
+    if osd_already_running(&osd_pid_file_path()) {
+        tracing::debug!("whspr-osd already running, not spawning another");
+        return None;
+    }
+    let osd_path = osd_path();
+    match osd_command(osd).spawn() {
         Ok(child) => {
             tracing::debug!("spawned whspr-osd (pid {})", child.id());
             Some(child)
@@ -114,10 +636,43 @@ fn spawn_osd() -> Option<Child> {
 }
 
 #[cfg(not(feature = "osd"))]
-fn spawn_osd() -> Option<Child> {
+fn spawn_osd(_osd: &OsdConfig) -> Option<Child> {
     None
 }
 
+/// Briefly show the transcript in the overlay. The text goes to whspr-osd
+/// through a pipe, never a file other users could read, and it exits on its
+/// own once it has faded out. A thread reaps it if we are still running.
+#[cfg(feature = "osd")]
+fn show_result_osd(text: &str, osd: &OsdConfig) {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    if !osd.autostart {
+        return;
+    }
+    let mut child = match osd_command(osd).arg("--text").stdin(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::warn!(
+                "failed to spawn whspr-osd from {}: {e}",
+                osd_path().display()
+            );
+            return;
+        }
+    };
+    // Dropping stdin closes the pipe, so whspr-osd sees the end of the text.
+    if let Some(mut stdin) = child.stdin.take()
+        && let Err(e) = stdin.write_all(text.as_bytes())
+    {
+        tracing::warn!("failed to pass the transcript to whspr-osd: {e}");
+    }
+    std::thread::spawn(move || child.wait());
+}
+
+#[cfg(not(feature = "osd"))]
+fn show_result_osd(_text: &str, _osd: &OsdConfig) {}
+
 fn kill_osd(child: &mut Option<Child>) {
     if let Some(mut c) = child.take() {
         let pid = c.id() as libc::pid_t;
@@ -132,6 +687,294 @@ fn kill_osd(child: &mut Option<Child>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use whspr_rs::audio::{CaptureSink, CaptureSource};
+
+    #[test]
+    fn is_too_short_compares_against_min_duration() {
+        assert!(is_too_short(1_600, 16_000, 300));
+        assert!(!is_too_short(4_800, 16_000, 300));
+        assert!(!is_too_short(0, 16_000, 0));
+    }
+
+    #[test]
+    fn state_file_tracks_transitions_and_is_removed_on_drop() {
+        let path = crate::test_support::unique_temp_path("app-state", "state");
+        let state = StateFile::new(Some(path.clone()), None);
+        state.set(AppState::Recording);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "recording");
+        state.set(AppState::Transcribing);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "transcribing");
+        drop(state);
+        assert!(!path.exists());
+    }
+
+    #[cfg(feature = "osd")]
+    #[test]
+    fn osd_already_running_checks_pid_file() {
+        let path = crate::test_support::unique_temp_path("osd-pid", "pid");
+        assert!(!osd_already_running(&path));
+        std::fs::write(&path, std::process::id().to_string()).unwrap();
+        assert!(osd_already_running(&path));
+        std::fs::write(&path, "99999999").unwrap();
+        assert!(!osd_already_running(&path));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// Answers every transcription with `reply` after `delay`, and notes the
+    /// published state while it works.
+    struct MockBackend {
+        reply: std::result::Result<&'static str, &'static str>,
+        delay: std::time::Duration,
+        state_path: PathBuf,
+        seen_state: std::sync::Mutex<Option<String>>,
+    }
+
+    impl MockBackend {
+        fn new(
+            reply: std::result::Result<&'static str, &'static str>,
+            state_path: &std::path::Path,
+        ) -> Self {
+            Self {
+                reply,
+                delay: std::time::Duration::from_millis(20),
+                state_path: state_path.to_path_buf(),
+                seen_state: std::sync::Mutex::new(None),
+            }
+        }
+    }
+
+    impl TranscriptionBackend for MockBackend {
+        fn transcribe(&self, _audio: &[f32], _sample_rate: u32) -> Result<String> {
+            std::thread::sleep(self.delay);
+            *self.seen_state.lock().unwrap() = std::fs::read_to_string(&self.state_path).ok();
+            self.reply
+                .map(str::to_string)
+                .map_err(|e| WhsprError::Transcription(e.to_string()))
+        }
+    }
+
+    /// Records injected text along with the state published at the time.
+    #[derive(Default)]
+    struct RecordingInjector {
+        state_path: PathBuf,
+        injected: std::sync::Mutex<Vec<(String, String)>>,
+    }
+
+    impl Inject for RecordingInjector {
+        async fn inject(&self, text: &str) -> Result<()> {
+            let state = std::fs::read_to_string(&self.state_path).unwrap_or_default();
+            self.injected
+                .lock()
+                .unwrap()
+                .push((text.to_string(), state));
+            Ok(())
+        }
+    }
+
+    fn test_config() -> Config {
+        let mut config = Config::default();
+        config.feedback.enabled = false;
+        config.osd.autostart = false;
+        config
+    }
+
+    /// Run one dictation through `Dictation::finish` with a mock backend
+    /// answering `reply`. Returns the result, what was injected (with the
+    /// state at that point) and the state seen during transcription.
+    async fn finish_with(
+        config: &Config,
+        reply: std::result::Result<&'static str, &'static str>,
+    ) -> (Result<()>, Vec<(String, String)>, Option<String>) {
+        let state_path = crate::test_support::unique_temp_path("app-dictation", "state");
+        let state = StateFile::new(Some(state_path.clone()), None);
+        let backend = Arc::new(MockBackend::new(reply, &state_path));
+        let injector = RecordingInjector {
+            state_path: state_path.clone(),
+            ..Default::default()
+        };
+        let replacements = replace::compile_rules(&config.replacements).unwrap();
+        let pipeline = Dictation {
+            config,
+            feedback: &FeedbackPlayer::new(&config.feedback),
+            state: &state,
+            focus_guard: &FocusGuard::new(&config.inject),
+            replacements: &replacements,
+            injector: &injector,
+            dry_run: false,
+        };
+        let mut model = ModelLoad::Done(Ok(backend.clone()));
+        let result = pipeline.finish(&mut model, vec![0.0; 16_000], 16_000).await;
+        let seen = backend.seen_state.lock().unwrap().clone();
+        (result, injector.injected.into_inner().unwrap(), seen)
+    }
+
+    #[tokio::test]
+    async fn dictation_injects_cleaned_up_transcript() {
+        let mut config = test_config();
+        config.replacements.insert(
+            "get hub".into(),
+            whspr_rs::config::Replacement::Literal("GitHub".into()),
+        );
+        config.postprocess.capitalize_first = true;
+
+        let (result, injected, seen) = finish_with(&config, Ok("push it to get hub")).await;
+        result.unwrap();
+        assert_eq!(seen.as_deref(), Some("transcribing"));
+        assert_eq!(
+            injected,
+            vec![("Push it to GitHub".to_string(), "injecting".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn dictation_drops_empty_and_blocklisted_transcripts() {
+        let mut config = test_config();
+        config.postprocess.blocklist = vec!["Thank you.".into()];
+        for reply in ["", "Thank you."] {
+            let (result, injected, _) = finish_with(&config, Ok(reply)).await;
+            result.unwrap();
+            assert!(injected.is_empty(), "{reply:?} was injected");
+        }
+    }
+
+    #[tokio::test]
+    async fn dictation_reports_transcription_errors_without_injecting() {
+        let (result, injected, _) = finish_with(&test_config(), Err("model exploded")).await;
+        assert!(matches!(result, Err(WhsprError::Transcription(e)) if e == "model exploded"));
+        assert!(injected.is_empty());
+    }
+
+    /// Captures a second of steady signal as soon as recording starts.
+    struct ToneSource;
+
+    impl CaptureSource for ToneSource {
+        fn start(&mut self, _sample_rate: u32, mut sink: CaptureSink) -> Result<()> {
+            sink.push_f32(&[0.1; 16_000], 1);
+            Ok(())
+        }
+
+        fn stop(&mut self) {}
+    }
+
+    /// Delivers each event once the state file shows the state it waits for,
+    /// so a script can say "toggle while transcribing".
+    struct ScriptedEvents {
+        state_path: PathBuf,
+        script: std::collections::VecDeque<(AppState, Event)>,
+    }
+
+    impl Events for ScriptedEvents {
+        async fn next(&mut self) -> Event {
+            loop {
+                if let Some(&(state, event)) = self.script.front()
+                    && std::fs::read_to_string(&self.state_path).is_ok_and(|s| s == state.as_str())
+                {
+                    self.script.pop_front();
+                    return event;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+            }
+        }
+    }
+
+    /// Run the dictation loop against `script`, with a mock backend answering
+    /// `reply`. Returns the result, what was injected (with the state at that
+    /// point) and how many transcriptions ran.
+    async fn run_with_script(
+        config: &Config,
+        reply: std::result::Result<&'static str, &'static str>,
+        script: Vec<(AppState, Event)>,
+    ) -> (Result<()>, Vec<(String, String)>, bool) {
+        let state_path = crate::test_support::unique_temp_path("app-loop", "state");
+        let state = StateFile::new(Some(state_path.clone()), None);
+        let backend = Arc::new(MockBackend::new(reply, &state_path));
+        let injector = RecordingInjector {
+            state_path: state_path.clone(),
+            ..Default::default()
+        };
+        let replacements = replace::compile_rules(&config.replacements).unwrap();
+        let pipeline = Dictation {
+            config,
+            feedback: &FeedbackPlayer::new(&config.feedback),
+            state: &state,
+            focus_guard: &FocusGuard::new(&config.inject),
+            replacements: &replacements,
+            injector: &injector,
+            dry_run: false,
+        };
+        let mut model = ModelLoad::Done(Ok(backend.clone()));
+        let mut events = ScriptedEvents {
+            state_path: state_path.clone(),
+            script: script.into(),
+        };
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            run_dictations(&pipeline, &mut model, &mut events, || {
+                AudioRecorder::with_source(&config.audio, Box::new(ToneSource))
+            }),
+        )
+        .await
+        .expect("dictation loop should finish");
+        assert!(
+            events.script.is_empty(),
+            "unused events: {:?}",
+            events.script
+        );
+        drop(state);
+        assert!(!state_path.exists());
+        let transcribed = backend.seen_state.lock().unwrap().is_some();
+        (result, injector.injected.into_inner().unwrap(), transcribed)
+    }
+
+    #[tokio::test]
+    async fn loop_records_until_toggle_then_injects() {
+        let script = vec![(AppState::Recording, Event::Toggle)];
+        let (result, injected, transcribed) =
+            run_with_script(&test_config(), Ok("hello there"), script).await;
+        result.unwrap();
+        assert!(transcribed);
+        assert_eq!(
+            injected,
+            vec![("hello there".to_string(), "injecting".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn loop_cancel_discards_the_recording() {
+        let script = vec![(AppState::Recording, Event::Cancel)];
+        let (result, injected, transcribed) =
+            run_with_script(&test_config(), Ok("hello there"), script).await;
+        result.unwrap();
+        assert!(!transcribed);
+        assert!(injected.is_empty());
+    }
+
+    #[tokio::test]
+    async fn loop_finishes_the_dictation_on_terminate() {
+        let script = vec![
+            (AppState::Recording, Event::Toggle),
+            (AppState::Transcribing, Event::Terminate),
+        ];
+        let (result, injected, _) =
+            run_with_script(&test_config(), Ok("hello there"), script).await;
+        result.unwrap();
+        assert_eq!(injected.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn loop_with_cooldown_records_again_after_a_toggle_while_busy() {
+        let mut config = test_config();
+        config.inject.cooldown_ms = 50;
+        let script = vec![
+            (AppState::Recording, Event::Toggle),
+            (AppState::Transcribing, Event::Toggle),
+            (AppState::Recording, Event::Stop),
+        ];
+        let (result, injected, _) = run_with_script(&config, Ok("again"), script).await;
+        result.unwrap();
+        let expected = ("again".to_string(), "injecting".to_string());
+        assert_eq!(injected, vec![expected.clone(), expected]);
+    }
 
     #[test]
     fn kill_osd_none_is_noop() {
@@ -1,54 +1,98 @@
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{SampleFormat, StreamConfig};
 
 use crate::config::AudioConfig;
 use crate::error::{Result, WhsprError};
+use crate::level_meter::{self, LevelMeter};
 
 const PREALLOC_SECONDS: usize = 120;
+/// Upper bound on normalization gain (+20 dB) so near-silence isn't blown up
+/// into loud noise that whisper hallucinates on.
+const MAX_NORMALIZE_GAIN: f32 = 10.0;
+
+/// Where a recording's audio comes from. `CpalSource` captures from an input
+/// device; anything else, e.g. synthetic frames in tests, goes through the
+/// same `CaptureSink` processing.
+pub trait CaptureSource {
+    /// Begin capturing at about `sample_rate`, handing every block of input
+    /// to `sink` until `stop`.
+    fn start(&mut self, sample_rate: u32, sink: CaptureSink) -> Result<()>;
+    fn stop(&mut self);
+}
 
-pub struct AudioRecorder {
-    config: AudioConfig,
+/// Takes raw input blocks from a `CaptureSource`, downmixes them to mono and
+/// applies the warmup, noise gate and length cap before buffering them.
+pub struct CaptureSink {
     buffer: Arc<Mutex<Vec<f32>>>,
+    max_samples: usize,
+    gate_threshold: f32,
+    warmup_remaining: usize,
+    meter: Option<LevelMeter>,
+}
+
+impl CaptureSink {
+    pub fn push_f32(&mut self, data: &[f32], channels: usize) {
+        self.push(|buf| append_mono_f32(data, channels, buf));
+    }
+
+    pub fn push_i16(&mut self, data: &[i16], channels: usize) {
+        self.push(|buf| append_mono_i16(data, channels, buf));
+    }
+
+    pub fn push_u16(&mut self, data: &[u16], channels: usize) {
+        self.push(|buf| append_mono_u16(data, channels, buf));
+    }
+
+    fn push(&mut self, append: impl FnOnce(&mut Vec<f32>)) {
+        let mut buf = lock_buffer(&self.buffer);
+        if buf.len() < self.max_samples {
+            let start = buf.len();
+            append(&mut buf);
+            discard_warmup(&mut buf, start, &mut self.warmup_remaining);
+            apply_noise_gate(&mut buf[start..], self.gate_threshold);
+            buf.truncate(self.max_samples);
+            if let (Some(meter), Some(block)) = (&self.meter, buf.get(start..)) {
+                meter.push(block);
+            }
+        }
+    }
+}
+
+/// Lock the sample buffer, recovering it if a panic in an audio callback
+/// poisoned the lock. The samples are plain data and still usable, so the
+/// recording is kept rather than lost.
+fn lock_buffer(buffer: &Mutex<Vec<f32>>) -> MutexGuard<'_, Vec<f32>> {
+    buffer.lock().unwrap_or_else(|poisoned| {
+        tracing::warn!("audio buffer was poisoned by a panic in the audio callback, recovering it");
+        buffer.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+/// Captures from the configured input device through cpal.
+pub struct CpalSource {
+    device: String,
     stream: Option<cpal::Stream>,
 }
 
-impl AudioRecorder {
-    pub fn new(config: &AudioConfig) -> Self {
+impl CpalSource {
+    /// `device` is an `[audio].device` spec; empty picks the default device.
+    pub fn new(device: &str) -> Self {
         Self {
-            config: config.clone(),
-            buffer: Arc::new(Mutex::new(Vec::new())),
+            device: device.to_string(),
             stream: None,
         }
     }
+}
 
-    pub fn start(&mut self) -> Result<()> {
-        let host = cpal::default_host();
-
-        let device = if self.config.device.is_empty() {
-            host.default_input_device()
-                .ok_or_else(|| WhsprError::Audio("no default input device found".into()))?
-        } else {
-            host.input_devices()
-                .map_err(|e| WhsprError::Audio(format!("failed to enumerate input devices: {e}")))?
-                .find(|d| {
-                    d.description()
-                        .map(|desc| desc.name().contains(&self.config.device))
-                        .unwrap_or(false)
-                })
-                .ok_or_else(|| {
-                    WhsprError::Audio(format!("input device '{}' not found", self.config.device))
-                })?
-        };
-
-        let device_name = device
-            .description()
-            .map(|d| d.name().to_string())
-            .unwrap_or_else(|_| "unknown".into());
-        tracing::info!("using input device: {device_name}");
+impl CaptureSource for CpalSource {
+    fn start(&mut self, sample_rate: u32, mut sink: CaptureSink) -> Result<()> {
+        let device = find_input_device(&self.device)?;
+        tracing::info!("using input device: {}", device_name(&device));
 
-        let (stream_config, sample_format) = choose_input_config(&device, self.config.sample_rate)?;
+        let (stream_config, sample_format) = choose_input_config(&device, sample_rate)?;
         if stream_config.channels != 1 {
             tracing::warn!(
                 "device input has {} channels; downmixing to mono",
@@ -61,20 +105,6 @@ impl AudioRecorder {
             stream_config.channels,
             sample_format
         );
-
-        let buffer = Arc::clone(&self.buffer);
-        {
-            let mut guard = buffer
-                .lock()
-                .map_err(|_| WhsprError::Audio("audio buffer lock poisoned".into()))?;
-            guard.clear();
-            let prealloc_samples =
-                (self.config.sample_rate as usize).saturating_mul(PREALLOC_SECONDS);
-            let capacity = guard.capacity();
-            if capacity < prealloc_samples {
-                guard.reserve_exact(prealloc_samples - capacity);
-            }
-        }
         let channels = stream_config.channels as usize;
 
         // The callback still takes a mutex on the realtime thread. Preallocation
@@ -85,48 +115,31 @@ impl AudioRecorder {
         };
 
         let stream = match sample_format {
-            SampleFormat::F32 => device
-                .build_input_stream(
-                    &stream_config,
-                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                        if let Ok(mut buf) = buffer.lock() {
-                            append_mono_f32(data, channels, &mut buf);
-                        }
-                    },
-                    err_fn,
-                    None,
-                )
-                .map_err(|e| WhsprError::Audio(format!("failed to build input stream: {e}")))?,
-            SampleFormat::I16 => device
-                .build_input_stream(
-                    &stream_config,
-                    move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                        if let Ok(mut buf) = buffer.lock() {
-                            append_mono_i16(data, channels, &mut buf);
-                        }
-                    },
-                    err_fn,
-                    None,
-                )
-                .map_err(|e| WhsprError::Audio(format!("failed to build input stream: {e}")))?,
-            SampleFormat::U16 => device
-                .build_input_stream(
-                    &stream_config,
-                    move |data: &[u16], _: &cpal::InputCallbackInfo| {
-                        if let Ok(mut buf) = buffer.lock() {
-                            append_mono_u16(data, channels, &mut buf);
-                        }
-                    },
-                    err_fn,
-                    None,
-                )
-                .map_err(|e| WhsprError::Audio(format!("failed to build input stream: {e}")))?,
+            SampleFormat::F32 => device.build_input_stream(
+                &stream_config,
+                move |data: &[f32], _: &cpal::InputCallbackInfo| sink.push_f32(data, channels),
+                err_fn,
+                None,
+            ),
+            SampleFormat::I16 => device.build_input_stream(
+                &stream_config,
+                move |data: &[i16], _: &cpal::InputCallbackInfo| sink.push_i16(data, channels),
+                err_fn,
+                None,
+            ),
+            SampleFormat::U16 => device.build_input_stream(
+                &stream_config,
+                move |data: &[u16], _: &cpal::InputCallbackInfo| sink.push_u16(data, channels),
+                err_fn,
+                None,
+            ),
             other => {
                 return Err(WhsprError::Audio(format!(
                     "unsupported input sample format: {other:?}"
                 )));
             }
-        };
+        }
+        .map_err(|e| WhsprError::Audio(format!("failed to build input stream: {e}")))?;
 
         stream
             .play()
@@ -139,11 +152,10 @@ impl AudioRecorder {
             std::mem::forget(old);
         }
         self.stream = Some(stream);
-        tracing::info!("audio recording started");
         Ok(())
     }
 
-    pub fn stop(&mut self) -> Result<Vec<f32>> {
+    fn stop(&mut self) {
         // Take and leak the stream — cpal's ALSA backend calls snd_pcm_close()
         // on drop without draining first, which causes an audible click on
         // PipeWire when the stream is still "warm".  The OS reclaims file
@@ -152,13 +164,85 @@ impl AudioRecorder {
             let _ = stream.pause();
             std::mem::forget(stream);
         }
+    }
+}
 
-        let mut buffer = std::mem::take(
-            &mut *self
-                .buffer
-                .lock()
-                .map_err(|_| WhsprError::Audio("audio buffer lock poisoned".into()))?,
-        );
+pub struct AudioRecorder {
+    config: AudioConfig,
+    buffer: Arc<Mutex<Vec<f32>>>,
+    source: Box<dyn CaptureSource>,
+    /// Whether to publish the input level for whspr-osd.
+    publish_level: bool,
+}
+
+impl AudioRecorder {
+    pub fn new(config: &AudioConfig) -> Self {
+        let mut recorder = Self::with_source(config, Box::new(CpalSource::new(&config.device)));
+        recorder.publish_level = true;
+        recorder
+    }
+
+    /// A recorder fed by `source` instead of an input device. It doesn't
+    /// publish the input level for whspr-osd.
+    pub fn with_source(config: &AudioConfig, source: Box<dyn CaptureSource>) -> Self {
+        Self {
+            config: config.clone(),
+            buffer: Arc::new(Mutex::new(Vec::new())),
+            source,
+            publish_level: false,
+        }
+    }
+
+    pub fn start(&mut self) -> Result<()> {
+        {
+            let mut guard = lock_buffer(&self.buffer);
+            guard.clear();
+            let prealloc_samples =
+                (self.config.sample_rate as usize).saturating_mul(PREALLOC_SECONDS);
+            let capacity = guard.capacity();
+            if capacity < prealloc_samples {
+                guard.reserve_exact(prealloc_samples - capacity);
+            }
+        }
+        // Lets the overlay show the level without opening the mic itself.
+        let meter = if self.publish_level {
+            match LevelMeter::create(&level_meter::meter_path()) {
+                Ok(meter) => Some(meter),
+                Err(e) => {
+                    tracing::warn!("failed to publish the input level for the overlay: {e}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let sink = CaptureSink {
+            buffer: Arc::clone(&self.buffer),
+            max_samples: max_samples(self.config.sample_rate, self.config.max_duration_secs),
+            gate_threshold: self.config.gate_threshold,
+            warmup_remaining: warmup_samples(self.config.sample_rate, self.config.warmup_ms),
+            meter,
+        };
+
+        self.source.start(self.config.sample_rate, sink)?;
+        tracing::info!("audio recording started");
+        Ok(())
+    }
+
+    /// Copy of the last `max_samples` samples captured so far, for partial
+    /// transcription while the recording continues.
+    pub fn tail(&self, max_samples: usize) -> Vec<f32> {
+        let buffer = lock_buffer(&self.buffer);
+        buffer[buffer.len().saturating_sub(max_samples)..].to_vec()
+    }
+
+    pub fn stop(&mut self) -> Result<Vec<f32>> {
+        self.source.stop();
+        if self.publish_level {
+            let _ = std::fs::remove_file(level_meter::meter_path());
+        }
+
+        let mut buffer = std::mem::take(&mut *lock_buffer(&self.buffer));
         tracing::info!("audio recording stopped, captured {} samples", buffer.len());
 
         if buffer.is_empty() {
@@ -174,10 +258,121 @@ impl AudioRecorder {
             buffer[start + i] *= gain;
         }
 
+        if self.config.normalize {
+            normalize_rms(&mut buffer, self.config.target_rms);
+        }
+
         Ok(buffer)
     }
 }
 
+/// Scale samples so their RMS approaches `target_rms`, clamping to [-1, 1].
+/// Gain is capped at `MAX_NORMALIZE_GAIN`.
+fn normalize_rms(samples: &mut [f32], target_rms: f32) {
+    if samples.is_empty() || target_rms <= 0.0 {
+        return;
+    }
+    let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+    if rms <= f32::EPSILON {
+        return;
+    }
+    let gain = (target_rms / rms).min(MAX_NORMALIZE_GAIN);
+    tracing::debug!("normalizing audio: RMS {rms:.4} -> {target_rms:.4} (gain {gain:.2})");
+    for sample in samples.iter_mut() {
+        *sample = (*sample * gain).clamp(-1.0, 1.0);
+    }
+}
+
+/// Zero a block of freshly captured samples if its RMS is below `threshold`.
+///
+/// Runs on the realtime audio thread once per callback, so the block (a few
+/// ms of audio) doubles as the short-term energy window. 0.0 disables the gate.
+fn apply_noise_gate(block: &mut [f32], threshold: f32) {
+    if threshold <= 0.0 || block.is_empty() {
+        return;
+    }
+    let mean_square = block.iter().map(|s| s * s).sum::<f32>() / block.len() as f32;
+    if mean_square < threshold * threshold {
+        block.fill(0.0);
+    }
+}
+
+/// Number of mono samples in `warmup_ms`.
+fn warmup_samples(sample_rate: u32, warmup_ms: u64) -> usize {
+    (sample_rate as u64)
+        .saturating_mul(warmup_ms)
+        .div_ceil(1000) as usize
+}
+
+/// Drop samples appended from `start` until `remaining` warm-up samples have
+/// been discarded.
+fn discard_warmup(buf: &mut Vec<f32>, start: usize, remaining: &mut usize) {
+    if *remaining == 0 {
+        return;
+    }
+    let n = (*remaining).min(buf.len() - start);
+    buf.drain(start..start + n);
+    *remaining -= n;
+}
+
+/// Buffer capacity limit for the configured maximum duration (0 = unbounded).
+fn max_samples(sample_rate: u32, max_duration_secs: u64) -> usize {
+    if max_duration_secs == 0 {
+        return usize::MAX;
+    }
+    (sample_rate as usize).saturating_mul(max_duration_secs as usize)
+}
+
+/// Parse an `index:N` device selector. Plain strings are name substrings and
+/// yield `None`.
+fn parse_device_index(spec: &str) -> Result<Option<usize>> {
+    let Some(index) = spec.strip_prefix("index:") else {
+        return Ok(None);
+    };
+    index.trim().parse().map(Some).map_err(|_| {
+        WhsprError::Config(format!(
+            "invalid audio device selector '{spec}', expected index:<number>"
+        ))
+    })
+}
+
+/// Resolve `audio.device`: empty for the default input, `index:N`, or a
+/// name substring.
+pub fn find_input_device(spec: &str) -> Result<cpal::Device> {
+    let host = cpal::default_host();
+
+    if spec.is_empty() {
+        host.default_input_device()
+            .ok_or_else(|| WhsprError::Audio("no default input device found".into()))
+    } else if let Some(index) = parse_device_index(spec)? {
+        host.input_devices()
+            .map_err(|e| WhsprError::Audio(format!("failed to enumerate input devices: {e}")))?
+            .nth(index)
+            .ok_or_else(|| {
+                WhsprError::Audio(format!(
+                    "no input device at index {index} (see `whspr-rs devices`)"
+                ))
+            })
+    } else {
+        host.input_devices()
+            .map_err(|e| WhsprError::Audio(format!("failed to enumerate input devices: {e}")))?
+            .find(|d| {
+                d.description()
+                    .map(|desc| desc.name().contains(spec))
+                    .unwrap_or(false)
+            })
+            .ok_or_else(|| WhsprError::Audio(format!("input device '{spec}' not found")))
+    }
+}
+
+/// A device's name as `whspr-rs devices` lists it and `audio.device` matches it.
+pub fn device_name(device: &cpal::Device) -> String {
+    device
+        .description()
+        .map(|d| d.name().to_string())
+        .unwrap_or_else(|_| "unknown".into())
+}
+
 fn choose_input_config(
     device: &cpal::Device,
     sample_rate: u32,
@@ -284,6 +479,110 @@ mod tests {
         (a - b).abs() <= eps
     }
 
+    #[test]
+    fn poisoned_buffer_keeps_the_recorded_samples() {
+        let source = FakeSource {
+            blocks: vec![vec![0.5; 160]],
+            channels: 1,
+        };
+        let mut recorder = AudioRecorder::with_source(&AudioConfig::default(), Box::new(source));
+        recorder.start().unwrap();
+
+        let buffer = Arc::clone(&recorder.buffer);
+        let _ = std::thread::spawn(move || {
+            let _guard = buffer.lock().unwrap();
+            panic!("simulated audio callback panic");
+        })
+        .join();
+        assert!(recorder.buffer.is_poisoned());
+
+        assert_eq!(recorder.tail(2), vec![0.5, 0.5]);
+        assert!(!recorder.buffer.is_poisoned());
+        let audio = recorder.stop().expect("samples survive the poisoned lock");
+        assert_eq!(audio.len(), 160);
+    }
+
+    #[test]
+    fn discard_warmup_spans_callbacks() {
+        assert_eq!(warmup_samples(16_000, 50), 800);
+        assert_eq!(warmup_samples(16_000, 0), 0);
+
+        let mut remaining = 3;
+        let mut buf = vec![1.0, 2.0];
+        discard_warmup(&mut buf, 0, &mut remaining);
+        assert!(buf.is_empty());
+        assert_eq!(remaining, 1);
+
+        buf.extend([3.0, 4.0, 5.0]);
+        discard_warmup(&mut buf, 0, &mut remaining);
+        assert_eq!(buf, vec![4.0, 5.0]);
+        assert_eq!(remaining, 0);
+
+        buf.push(6.0);
+        discard_warmup(&mut buf, 2, &mut remaining);
+        assert_eq!(buf, vec![4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn tail_copies_latest_samples_without_draining() {
+        let source = FakeSource {
+            blocks: vec![vec![0.1, 0.2, 0.3, 0.4]],
+            channels: 1,
+        };
+        let mut recorder = AudioRecorder::with_source(&AudioConfig::default(), Box::new(source));
+        recorder.start().unwrap();
+        assert_eq!(recorder.tail(2), vec![0.3, 0.4]);
+        assert_eq!(recorder.tail(10), vec![0.1, 0.2, 0.3, 0.4]);
+        assert_eq!(recorder.buffer.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn max_samples_is_unbounded_when_disabled() {
+        assert_eq!(max_samples(16000, 0), usize::MAX);
+        assert_eq!(max_samples(16000, 10), 160_000);
+    }
+
+    #[test]
+    fn normalize_rms_reaches_target() {
+        let mut samples: Vec<f32> = (0..1600).map(|i| 0.02 * (i as f32 * 0.05).sin()).collect();
+        normalize_rms(&mut samples, 0.1);
+        let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+        assert!(approx_eq(rms, 0.1, 1e-3), "rms was {rms}");
+    }
+
+    #[test]
+    fn normalize_rms_caps_gain_and_clamps() {
+        let mut quiet = vec![0.001, -0.001];
+        normalize_rms(&mut quiet, 0.5);
+        assert!(approx_eq(quiet[0], 0.001 * MAX_NORMALIZE_GAIN, 1e-6));
+
+        let mut loud = vec![0.9, -0.1, 0.1, -0.1];
+        normalize_rms(&mut loud, 0.9);
+        assert!(loud.iter().all(|s| (-1.0..=1.0).contains(s)));
+    }
+
+    #[test]
+    fn apply_noise_gate_zeroes_quiet_blocks_only() {
+        let mut quiet = vec![0.001, -0.002, 0.001];
+        apply_noise_gate(&mut quiet, 0.01);
+        assert_eq!(quiet, vec![0.0, 0.0, 0.0]);
+
+        let mut loud = vec![0.2, -0.3, 0.1];
+        apply_noise_gate(&mut loud, 0.01);
+        assert_eq!(loud, vec![0.2, -0.3, 0.1]);
+
+        let mut disabled = vec![0.001, -0.002];
+        apply_noise_gate(&mut disabled, 0.0);
+        assert_eq!(disabled, vec![0.001, -0.002]);
+    }
+
+    #[test]
+    fn parse_device_index_accepts_index_prefix_only() {
+        assert_eq!(parse_device_index("index:2").unwrap(), Some(2));
+        assert_eq!(parse_device_index("USB Mic").unwrap(), None);
+        assert!(parse_device_index("index:two").is_err());
+    }
+
     #[test]
     fn append_mono_f32_passthrough_for_single_channel() {
         let mut out = Vec::new();
@@ -313,4 +612,61 @@ mod tests {
         append_mono_u16(&[0, u16::MAX], 2, &mut out);
         assert!(approx_eq(out[0], 0.0, 0.01));
     }
+
+    /// Delivers `blocks` of interleaved frames as soon as recording starts.
+    struct FakeSource {
+        blocks: Vec<Vec<f32>>,
+        channels: usize,
+    }
+
+    impl CaptureSource for FakeSource {
+        fn start(&mut self, _sample_rate: u32, mut sink: CaptureSink) -> Result<()> {
+            for block in &self.blocks {
+                sink.push_f32(block, self.channels);
+            }
+            Ok(())
+        }
+
+        fn stop(&mut self) {}
+    }
+
+    fn record(config: &AudioConfig, blocks: Vec<Vec<f32>>, channels: usize) -> Result<Vec<f32>> {
+        let source = FakeSource { blocks, channels };
+        let mut recorder = AudioRecorder::with_source(config, Box::new(source));
+        recorder.start()?;
+        recorder.stop()
+    }
+
+    #[test]
+    fn recorder_downmixes_and_fades_out_the_end() {
+        let config = AudioConfig::default();
+        // 100ms of stereo at 0.5 in both channels, in two blocks.
+        let block = vec![0.5; 1600];
+        let audio = record(&config, vec![block.clone(), block], 2).unwrap();
+        assert_eq!(audio.len(), 1600);
+        assert!(approx_eq(audio[0], 0.5, 1e-6));
+        // The last 5ms (80 samples) ramp down towards silence.
+        assert!(approx_eq(audio[1600 - 81], 0.5, 1e-6));
+        assert!(audio[1600 - 40] < 0.5 && audio[1600 - 40] > 0.0);
+        assert!(audio[1599] < 0.01);
+    }
+
+    #[test]
+    fn recorder_drops_warmup_and_caps_length() {
+        let config = AudioConfig {
+            warmup_ms: 100,
+            max_duration_secs: 1,
+            ..AudioConfig::default()
+        };
+        let ramp: Vec<f32> = (0..20_000).map(|i| i as f32 / 20_000.0).collect();
+        let audio = record(&config, vec![ramp], 1).unwrap();
+        assert_eq!(audio.len(), 16_000);
+        assert!(approx_eq(audio[0], 1_600.0 / 20_000.0, 1e-6));
+    }
+
+    #[test]
+    fn recorder_reports_when_nothing_was_captured() {
+        let err = record(&AudioConfig::default(), Vec::new(), 1).unwrap_err();
+        assert!(matches!(err, WhsprError::Audio(msg) if msg == "no audio data captured"));
+    }
 }
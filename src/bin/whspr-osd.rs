@@ -2,16 +2,21 @@ use std::os::fd::AsRawFd;
 use std::os::unix::io::{AsFd, FromRawFd};
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
 use std::time::Instant;
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use wayland_client::protocol::{
-    wl_buffer, wl_compositor, wl_registry, wl_shm, wl_shm_pool, wl_surface,
+    wl_buffer, wl_compositor, wl_output, wl_pointer, wl_registry, wl_seat, wl_shm, wl_shm_pool,
+    wl_surface,
 };
-use wayland_client::{Connection, Dispatch, QueueHandle, delegate_noop};
+use wayland_client::{Connection, Dispatch, QueueHandle, WEnum, delegate_noop};
 use wayland_protocols_wlr::layer_shell::v1::client::{zwlr_layer_shell_v1, zwlr_layer_surface_v1};
 
+use whspr_rs::level_meter::{self, LevelMeter};
+use whspr_rs::osd_config::{self, Anchor, Color, OSD_CONFIG_ENV, OsdConfig, OsdStyle};
+use whspr_rs::runtime::runtime_file;
+
 // --- Layout ---
 const NUM_BARS: usize = 28;
 const BAR_WIDTH: u32 = 3;
@@ -22,34 +27,40 @@ const BAR_MIN_HEIGHT: f32 = 2.0;
 const BAR_MAX_HEIGHT: f32 = 30.0;
 const OSD_WIDTH: u32 = PAD_X * 2 + NUM_BARS as u32 * BAR_WIDTH + (NUM_BARS as u32 - 1) * BAR_GAP;
 const OSD_HEIGHT: u32 = BAR_MAX_HEIGHT as u32 + PAD_Y * 2;
-const MARGIN_BOTTOM: i32 = 40;
 const CORNER_RADIUS: u32 = 12;
 const BORDER_WIDTH: u32 = 1;
 const RISE_RATE: f32 = 0.55;
 const DECAY_RATE: f32 = 0.88;
 
+// --- Waveform (`style = "waveform"`) ---
+const WAVEFORM_SAMPLES: usize = level_meter::METER_SAMPLES;
+const WAVEFORM_GAIN: f32 = 4.0;
+
+// --- Result text (`--text` mode) ---
+const TEXT_SCALE: u32 = 2;
+const GLYPH_W: u32 = 5;
+const GLYPH_H: u32 = 7;
+const TEXT_ADVANCE: u32 = (GLYPH_W + 1) * TEXT_SCALE;
+const TEXT_LINE_HEIGHT: u32 = (GLYPH_H + 3) * TEXT_SCALE;
+const TEXT_MAX_COLS: usize = 48;
+const TEXT_MAX_LINES: usize = 3;
+const TEXT_SHOW_SECS: f32 = 2.5;
+const TEXT_FADE_SECS: f32 = 0.4;
+
+// --- Input ---
+/// linux/input-event-codes.h
+const BTN_LEFT: u32 = 0x110;
+
 // --- Animation ---
 const FPS: i32 = 30;
 const FRAME_MS: i32 = 1000 / FPS;
-
-// --- Colors ---
-const BG_R: u8 = 18;
-const BG_G: u8 = 18;
-const BG_B: u8 = 30;
-const BG_A: u8 = 185;
-
-const BORDER_R: u8 = 140;
-const BORDER_G: u8 = 180;
-const BORDER_B: u8 = 255;
-const BORDER_A: u8 = 40;
-
-// Bar gradient: teal → violet
-const BAR_LEFT_R: f32 = 0.0;
-const BAR_LEFT_G: f32 = 0.82;
-const BAR_LEFT_B: f32 = 0.75;
-const BAR_RIGHT_R: f32 = 0.65;
-const BAR_RIGHT_G: f32 = 0.35;
-const BAR_RIGHT_B: f32 = 1.0;
+/// Frame rate once the mic has been quiet for `QUIET_SECS`, to save power.
+const QUIET_FPS: i32 = 5;
+const QUIET_FRAME_MS: i32 = 1000 / QUIET_FPS;
+/// Input level below which the mic counts as quiet.
+const QUIET_RMS: f32 = 0.01;
+/// Long enough for the bars to settle before the frame rate drops.
+const QUIET_SECS: f32 = 1.0;
 
 static SHOULD_EXIT: AtomicBool = AtomicBool::new(false);
 
@@ -72,6 +83,37 @@ impl AudioLevel {
     }
 }
 
+/// The most recent mono samples, oldest overwritten first. Only the capture
+/// thread writes, so plain atomics are enough.
+struct SampleRing {
+    samples: Box<[AtomicU32]>,
+    write_pos: AtomicUsize,
+}
+
+impl SampleRing {
+    fn new() -> Self {
+        Self {
+            samples: (0..WAVEFORM_SAMPLES).map(|_| AtomicU32::new(0)).collect(),
+            write_pos: AtomicUsize::new(0),
+        }
+    }
+
+    fn push(&self, sample: f32) {
+        let pos = self.write_pos.load(Ordering::Relaxed);
+        self.samples[pos % WAVEFORM_SAMPLES].store(sample.to_bits(), Ordering::Relaxed);
+        self.write_pos.store(pos.wrapping_add(1), Ordering::Relaxed);
+    }
+
+    /// Copy the buffer into `out`, oldest sample first.
+    fn snapshot(&self, out: &mut [f32; WAVEFORM_SAMPLES]) {
+        let start = self.write_pos.load(Ordering::Relaxed);
+        for (i, slot) in out.iter_mut().enumerate() {
+            let bits = self.samples[(start + i) % WAVEFORM_SAMPLES].load(Ordering::Relaxed);
+            *slot = f32::from_bits(bits);
+        }
+    }
+}
+
 // --- Bar animation state ---
 struct BarState {
     heights: [f32; NUM_BARS],
@@ -109,23 +151,106 @@ impl BarState {
     }
 }
 
+/// What the overlay shows: the live visualizer while recording, or the
+/// transcript for a moment after dictation.
+enum Mode {
+    Visualizer,
+    Text(Vec<String>),
+}
+
+impl Mode {
+    fn size(&self) -> (u32, u32) {
+        match self {
+            Mode::Visualizer => (OSD_WIDTH, OSD_HEIGHT),
+            Mode::Text(lines) => {
+                let cols = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as u32;
+                let width = (PAD_X * 2 + cols * TEXT_ADVANCE).max(OSD_WIDTH);
+                let height = PAD_Y * 2 + lines.len() as u32 * TEXT_LINE_HEIGHT;
+                (width, height)
+            }
+        }
+    }
+}
+
 // --- Wayland state ---
 struct OsdState {
     running: bool,
+    /// Surface size in logical pixels.
     width: u32,
     height: u32,
+    /// Buffer pixels per logical pixel, from `wl_surface.preferred_buffer_scale`.
+    scale: u32,
     compositor: Option<wl_compositor::WlCompositor>,
     shm: Option<wl_shm::WlShm>,
     layer_shell: Option<zwlr_layer_shell_v1::ZwlrLayerShellV1>,
+    /// Outputs with the name they announced (wl_output v4+).
+    outputs: Vec<(wl_output::WlOutput, Option<String>)>,
     surface: Option<wl_surface::WlSurface>,
     layer_surface: Option<zwlr_layer_surface_v1::ZwlrLayerSurfaceV1>,
     buffer: Option<wl_buffer::WlBuffer>,
     configured: bool,
+    seat: Option<wl_seat::WlSeat>,
+    pointer: Option<wl_pointer::WlPointer>,
+    /// Whether a click stops the recording (the visualizer, not the result text).
+    clickable: bool,
 }
 
 fn pid_file_path() -> PathBuf {
-    let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".into());
-    PathBuf::from(runtime_dir).join("whspr-osd.pid")
+    runtime_file("whspr-osd.pid")
+}
+
+/// Stop the recording of the running whspr-rs, as `kill -USR2` would.
+fn stop_recording() {
+    let Some(pid) = std::fs::read_to_string(runtime_file("whspr-rs.pid"))
+        .ok()
+        .and_then(|s| s.trim().parse::<libc::pid_t>().ok())
+    else {
+        eprintln!("whspr-osd: no running whspr-rs to stop");
+        return;
+    };
+    // A stale pid file may name some other process by now.
+    let comm = std::fs::read_to_string(format!("/proc/{pid}/comm")).unwrap_or_default();
+    if comm.trim() != "whspr-rs" {
+        eprintln!("whspr-osd: pid {pid} is not whspr-rs, not stopping it");
+        return;
+    }
+    if unsafe { libc::kill(pid, libc::SIGUSR2) } != 0 {
+        eprintln!(
+            "whspr-osd: failed to signal whspr-rs (pid {pid}): {}",
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+/// `[osd]` settings from whspr-rs. When run standalone, they come from the
+/// default config file instead, or are the defaults if there is none.
+fn load_osd_config() -> OsdConfig {
+    if let Ok(encoded) = std::env::var(OSD_CONFIG_ENV) {
+        return toml::from_str(&encoded).unwrap_or_else(|e| {
+            eprintln!("invalid {OSD_CONFIG_ENV}, using defaults: {e}");
+            OsdConfig::default()
+        });
+    }
+    let path = whspr_rs::config::default_config_path();
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return OsdConfig::default(),
+        Err(e) => {
+            eprintln!("failed to read {}, using defaults: {e}", path.display());
+            return OsdConfig::default();
+        }
+    };
+    osd_config::from_config_file(&contents).unwrap_or_else(|e| {
+        eprintln!("invalid {}, using defaults: {e}", path.display());
+        OsdConfig::default()
+    })
+}
+
+/// The transcript whspr-rs pipes to `whspr-osd --text`.
+fn read_result_text() -> Option<Vec<String>> {
+    let text = std::io::read_to_string(std::io::stdin()).ok()?;
+    let lines = wrap_text(&fit_to_font(&text), TEXT_MAX_COLS, TEXT_MAX_LINES);
+    (!lines.is_empty()).then_some(lines)
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -140,11 +265,45 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         );
     }
 
-    let _ = std::fs::write(pid_file_path(), std::process::id().to_string());
+    let mode = if std::env::args().any(|arg| arg == "--text") {
+        match read_result_text() {
+            Some(lines) => Mode::Text(lines),
+            None => return Ok(()),
+        }
+    } else {
+        Mode::Visualizer
+    };
+    let (osd_width, osd_height) = mode.size();
+    let theme = load_osd_config();
+
+    // The pid file tracks the recording overlay only; a result overlay may
+    // still be fading out when the next recording starts.
+    let is_visualizer = matches!(mode, Mode::Visualizer);
+    if is_visualizer {
+        let _ = std::fs::write(pid_file_path(), std::process::id().to_string());
+    }
 
-    // Start audio capture for visualization
+    // Prefer the level whspr-rs publishes: opening the mic a second time
+    // fails on some ALSA setups. Capture it ourselves when run standalone.
+    let meter = if is_visualizer {
+        LevelMeter::open(&level_meter::meter_path()).ok()
+    } else {
+        None
+    };
     let audio_level = Arc::new(AudioLevel::new());
-    let _audio_stream = start_audio_capture(Arc::clone(&audio_level));
+    let sample_ring = Arc::new(SampleRing::new());
+    let audio_stream = if is_visualizer && meter.is_none() {
+        match start_audio_capture(Arc::clone(&audio_level), Arc::clone(&sample_ring)) {
+            Ok(stream) => Some(stream),
+            Err(reason) => {
+                eprintln!("no microphone for the visualizer ({reason}), showing an idle animation");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let idle = is_visualizer && meter.is_none() && audio_stream.is_none();
 
     // Wayland setup
     let conn = Connection::connect_to_env()?;
@@ -155,18 +314,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut state = OsdState {
         running: true,
-        width: OSD_WIDTH,
-        height: OSD_HEIGHT,
+        width: osd_width,
+        height: osd_height,
+        scale: 1,
         compositor: None,
         shm: None,
         layer_shell: None,
+        outputs: Vec::new(),
         surface: None,
         layer_surface: None,
         buffer: None,
         configured: false,
+        seat: None,
+        pointer: None,
+        clickable: is_visualizer,
     };
 
     event_queue.roundtrip(&mut state)?;
+    // Second roundtrip delivers the events of the outputs bound above
+    event_queue.roundtrip(&mut state)?;
+
+    let output = select_output(&state.outputs, &theme.output);
 
     // Create layer surface
     let compositor = state
@@ -181,16 +349,23 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let surface = compositor.create_surface(&qh, ());
     let layer_surface = layer_shell.get_layer_surface(
         &surface,
-        None,
+        output.as_ref(),
         zwlr_layer_shell_v1::Layer::Overlay,
         "whspr-osd".to_string(),
         &qh,
         (),
     );
 
-    layer_surface.set_size(OSD_WIDTH, OSD_HEIGHT);
-    layer_surface.set_anchor(zwlr_layer_surface_v1::Anchor::Bottom);
-    layer_surface.set_margin(0, 0, MARGIN_BOTTOM, 0);
+    layer_surface.set_size(osd_width, osd_height);
+    let margin = theme.margin;
+    let (anchor, (top, right, bottom, left)) = match theme.anchor {
+        Anchor::Top => (zwlr_layer_surface_v1::Anchor::Top, (margin, 0, 0, 0)),
+        Anchor::Bottom => (zwlr_layer_surface_v1::Anchor::Bottom, (0, 0, margin, 0)),
+        Anchor::Left => (zwlr_layer_surface_v1::Anchor::Left, (0, 0, 0, margin)),
+        Anchor::Right => (zwlr_layer_surface_v1::Anchor::Right, (0, margin, 0, 0)),
+    };
+    layer_surface.set_anchor(anchor);
+    layer_surface.set_margin(top, right, bottom, left);
     layer_surface.set_exclusive_zone(-1);
     layer_surface.set_keyboard_interactivity(zwlr_layer_surface_v1::KeyboardInteractivity::None);
     surface.commit();
@@ -202,14 +377,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Animation state
     let mut bars = BarState::new();
+    let mut waveform = [0.0f32; WAVEFORM_SAMPLES];
     let start_time = Instant::now();
 
-    // Reusable pixel buffer (avoids alloc/dealloc per frame)
-    let mut pixels = vec![0u8; (OSD_WIDTH * OSD_HEIGHT * 4) as usize];
+    // Reusable pixel buffer (avoids alloc/dealloc per frame); resized when
+    // the compositor changes the buffer scale
+    let mut pixels = Vec::new();
 
     // Persistent shm pool: create memfd + pool once, reuse each frame
-    let stride = OSD_WIDTH * 4;
-    let shm_size = (stride * OSD_HEIGHT) as i32;
+    let mut shm_size = (osd_width * osd_height * 4) as i32;
     let shm_fd = unsafe { libc::memfd_create(c"whspr-osd".as_ptr(), libc::MFD_CLOEXEC) };
     if shm_fd < 0 {
         return Err(std::io::Error::last_os_error().into());
@@ -222,6 +398,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .ok_or("wl_shm not advertised by wayland server")?;
     let pool = shm.create_pool(shm_file.as_fd(), shm_size, &qh, ());
 
+    let mut pacer = FramePacer::new();
+    let mut frame_ms = FRAME_MS;
+
     // Main animation loop
     while state.running && !SHOULD_EXIT.load(Ordering::Relaxed) {
         conn.flush()?;
@@ -236,7 +415,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             revents: 0,
         };
 
-        let ret = unsafe { libc::poll(&mut pollfd, 1, FRAME_MS) };
+        let ret = unsafe { libc::poll(&mut pollfd, 1, frame_ms) };
         if ret > 0 {
             let _ = read_guard.read();
         } else {
@@ -250,17 +429,56 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         // Update animation
         let time = start_time.elapsed().as_secs_f32();
-        let rms = audio_level.get();
-        bars.update(rms, time);
+        let scale = state.scale;
+        let w = state.width * scale;
+        let h = state.height * scale;
+        let frame_size = (w * h * 4) as i32;
+        if frame_size > shm_size {
+            shm_file.set_len(frame_size as u64)?;
+            pool.resize(frame_size);
+            shm_size = frame_size;
+        }
+        pixels.resize(frame_size as usize, 0);
+        pixels.fill(0);
+
+        // The real input level, if the visualizer is showing one
+        let level = match (&mode, &meter) {
+            (Mode::Visualizer, Some(meter)) => Some(meter.rms()),
+            (Mode::Visualizer, None) if !idle => Some(audio_level.get()),
+            _ => None,
+        };
+        frame_ms = pacer.frame_ms(level, time);
 
         // Render frame into reusable buffer
-        let w = state.width;
-        let h = state.height;
-        pixels.fill(0);
-        render_frame(&mut pixels, w, h, &bars, time);
+        match &mode {
+            Mode::Visualizer => match theme.style {
+                OsdStyle::Bars => {
+                    let rms = level.unwrap_or_else(|| idle_rms(time));
+                    bars.update(rms, time);
+                    render_frame(&mut pixels, w, h, scale, &bars, &theme);
+                }
+                OsdStyle::Waveform => {
+                    if let Some(meter) = &meter {
+                        meter.snapshot(&mut waveform);
+                    } else if idle {
+                        idle_waveform(&mut waveform, time);
+                    } else {
+                        sample_ring.snapshot(&mut waveform);
+                    }
+                    render_waveform_frame(&mut pixels, w, h, scale, &waveform, &theme);
+                }
+            },
+            Mode::Text(lines) => {
+                if time >= TEXT_SHOW_SECS + TEXT_FADE_SECS {
+                    break;
+                }
+                let fade = 1.0 - ((time - TEXT_SHOW_SECS) / TEXT_FADE_SECS).clamp(0.0, 1.0);
+                render_text_frame(&mut pixels, w, h, scale, lines, &theme, fade);
+            }
+        }
 
         // Present frame using persistent shm pool
-        if let Err(e) = present_frame(&mut state, &qh, &pool, &shm_file, &pixels, w, h) {
+        if let Err(e) = present_frame(&mut state, &qh, &pool, &shm_file, &pixels, w, h, scale) {
             eprintln!("frame dropped: {e}");
         }
     }
@@ -276,7 +494,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     if let Some(b) = state.buffer.take() {
         b.destroy();
     }
-    let _ = std::fs::remove_file(pid_file_path());
+    if is_visualizer {
+        let _ = std::fs::remove_file(pid_file_path());
+    }
     Ok(())
 }
 
@@ -286,9 +506,14 @@ unsafe extern "C" fn handle_signal(_sig: libc::c_int) {
 
 // --- Audio capture ---
 
-fn start_audio_capture(level: Arc<AudioLevel>) -> Option<cpal::Stream> {
+fn start_audio_capture(
+    level: Arc<AudioLevel>,
+    ring: Arc<SampleRing>,
+) -> Result<cpal::Stream, String> {
     let host = cpal::default_host();
-    let device = host.default_input_device()?;
+    let device = host
+        .default_input_device()
+        .ok_or("no default input device")?;
 
     // Try to find a supported config at 16kHz, preferring mono then fewer channels
     let config = device
@@ -324,82 +549,109 @@ fn start_audio_capture(level: Arc<AudioLevel>) -> Option<cpal::Stream> {
                 if sample_count == 0 {
                     return;
                 }
-                let sum: f32 = if channels <= 1 {
-                    data.iter().map(|s| s * s).sum()
-                } else {
-                    data.chunks(channels)
-                        .map(|frame| {
-                            let mono: f32 = frame.iter().sum::<f32>() / frame.len() as f32;
-                            mono * mono
-                        })
-                        .sum()
-                };
+                let mut sum = 0.0f32;
+                for frame in data.chunks(channels.max(1)) {
+                    let mono = frame.iter().sum::<f32>() / frame.len() as f32;
+                    ring.push(mono);
+                    sum += mono * mono;
+                }
                 let rms = (sum / sample_count as f32).sqrt();
                 level.set(rms);
             },
             |err| eprintln!("audio capture error: {err}"),
             None,
         )
-        .ok()?;
+        .map_err(|e| format!("failed to open input stream: {e}"))?;
+
+    stream
+        .play()
+        .map_err(|e| format!("failed to start input stream: {e}"))?;
+    Ok(stream)
+}
+
+/// A slow breathing level for the bars when there is no microphone, so the
+/// overlay still shows that recording is in progress.
+fn idle_rms(time: f32) -> f32 {
+    0.05 + 0.03 * (time * 2.0).sin()
+}
+
+/// Picks the poll timeout: full rate while there is sound, `QUIET_FPS` after
+/// the input has stayed below `QUIET_RMS` for `QUIET_SECS`.
+struct FramePacer {
+    last_active: f32,
+}
 
-    stream.play().ok()?;
-    Some(stream)
+impl FramePacer {
+    fn new() -> Self {
+        Self { last_active: 0.0 }
+    }
+
+    /// `rms` is `None` when nothing measured is shown, which keeps full rate.
+    fn frame_ms(&mut self, rms: Option<f32>, time: f32) -> i32 {
+        match rms {
+            Some(rms) if rms < QUIET_RMS => {
+                if time - self.last_active >= QUIET_SECS {
+                    return QUIET_FRAME_MS;
+                }
+            }
+            _ => self.last_active = time,
+        }
+        FRAME_MS
+    }
+}
+
+/// The waveform counterpart of `idle_rms`: a gentle travelling sine.
+fn idle_waveform(out: &mut [f32; WAVEFORM_SAMPLES], time: f32) {
+    let amplitude = idle_rms(time) * 0.5;
+    for (i, sample) in out.iter_mut().enumerate() {
+        let t = i as f32 / WAVEFORM_SAMPLES as f32;
+        *sample = amplitude * (t * std::f32::consts::TAU * 3.0 - time * 4.0).sin();
+    }
 }
 
 // --- Rendering ---
 
-fn render_frame(pixels: &mut [u8], w: u32, h: u32, bars: &BarState, _time: f32) {
-    // Glassmorphic background
-    draw_rounded_rect(
-        pixels,
-        w,
-        h,
-        0,
-        0,
-        w,
-        h,
-        CORNER_RADIUS,
-        BG_R,
-        BG_G,
-        BG_B,
-        BG_A,
-    );
-    draw_rounded_border(
-        pixels,
-        w,
-        h,
-        CORNER_RADIUS,
-        BORDER_WIDTH,
-        BORDER_R,
-        BORDER_G,
-        BORDER_B,
-        BORDER_A,
-    );
+// Layout constants are in logical pixels; `s` is the buffer scale they are
+// multiplied by.
+
+/// Glassmorphic background shared by both modes; `fade` scales its opacity.
+fn render_background(pixels: &mut [u8], w: u32, h: u32, s: u32, theme: &OsdConfig, fade: f32) {
+    let alpha = |a: u8| (a as f32 * fade) as u8;
+    let radius = CORNER_RADIUS * s;
+    let Color { r, g, b, a } = theme.background;
+    draw_rounded_rect(pixels, w, h, 0, 0, w, h, radius, r, g, b, alpha(a));
+    let Color { r, g, b, a } = theme.border;
+    draw_rounded_border(pixels, w, h, radius, BORDER_WIDTH * s, r, g, b, alpha(a));
 
     // Top highlight (glass reflection)
-    for x in (CORNER_RADIUS + 2)..(w.saturating_sub(CORNER_RADIUS + 2)) {
-        set_pixel_blend(pixels, w, h, x, 1, 255, 255, 255, 18);
+    for y in s..2 * s {
+        for x in (radius + 2 * s)..(w.saturating_sub(radius + 2 * s)) {
+            set_pixel_blend(pixels, w, h, x, y, 255, 255, 255, alpha(18));
+        }
     }
+}
+
+fn render_frame(pixels: &mut [u8], w: u32, h: u32, s: u32, bars: &BarState, theme: &OsdConfig) {
+    render_background(pixels, w, h, s, theme, 1.0);
+    let (left, right) = (theme.bar_left, theme.bar_right);
+    let bar_w = BAR_WIDTH * s;
 
     // Visualizer bars
     let center_y = h / 2;
     for i in 0..NUM_BARS {
-        let bx = PAD_X + i as u32 * (BAR_WIDTH + BAR_GAP);
-        let bar_h = bars.heights[i] as u32;
+        let bx = (PAD_X + i as u32 * (BAR_WIDTH + BAR_GAP)) * s;
+        let bar_h = (bars.heights[i] * s as f32) as u32;
         let half_h = bar_h / 2;
         let top_y = center_y.saturating_sub(half_h);
 
         let t = i as f32 / (NUM_BARS - 1) as f32;
-        let r = lerp(BAR_LEFT_R, BAR_RIGHT_R, t);
-        let g = lerp(BAR_LEFT_G, BAR_RIGHT_G, t);
-        let b = lerp(BAR_LEFT_B, BAR_RIGHT_B, t);
-        let cr = (r * 255.0) as u8;
-        let cg = (g * 255.0) as u8;
-        let cb = (b * 255.0) as u8;
+        let cr = lerp(left.r as f32, right.r as f32, t) as u8;
+        let cg = lerp(left.g as f32, right.g as f32, t) as u8;
+        let cb = lerp(left.b as f32, right.b as f32, t) as u8;
 
         // Glow
-        for gy in top_y.saturating_sub(2)..=(top_y + bar_h + 2).min(h - 1) {
-            for gx in bx.saturating_sub(1)..=(bx + BAR_WIDTH).min(w - 1) {
+        for gy in top_y.saturating_sub(2 * s)..=(top_y + bar_h + 2 * s).min(h - 1) {
+            for gx in bx.saturating_sub(s)..=(bx + bar_w).min(w - 1) {
                 set_pixel_blend(pixels, w, h, gx, gy, cr, cg, cb, 25);
             }
         }
@@ -409,13 +661,307 @@ fn render_frame(pixels: &mut [u8], w: u32, h: u32, bars: &BarState, _time: f32)
             let vy = (y as f32 - top_y as f32) / bar_h.max(1) as f32;
             let brightness = 1.0 - (vy - 0.5).abs() * 0.6;
             let a = (brightness * 230.0) as u8;
-            for x in bx..(bx + BAR_WIDTH).min(w) {
+            for x in bx..(bx + bar_w).min(w) {
                 set_pixel_blend(pixels, w, h, x, y, cr, cg, cb, a);
             }
         }
     }
 }
 
+/// Plot the recent samples, one min/max column per pixel.
+fn render_waveform_frame(
+    pixels: &mut [u8],
+    w: u32,
+    h: u32,
+    s: u32,
+    samples: &[f32],
+    theme: &OsdConfig,
+) {
+    render_background(pixels, w, h, s, theme, 1.0);
+    let (left, right) = (theme.bar_left, theme.bar_right);
+
+    let columns = w.saturating_sub(PAD_X * 2 * s) as usize;
+    if columns == 0 {
+        return;
+    }
+    let center_y = (h / 2) as f32;
+    let half_h = BAR_MAX_HEIGHT * s as f32 / 2.0;
+    let to_y = |s: f32| (center_y - (s * WAVEFORM_GAIN).clamp(-1.0, 1.0) * half_h) as u32;
+
+    for col in 0..columns {
+        let bucket = &samples[col * samples.len() / columns..(col + 1) * samples.len() / columns];
+        let (lo, hi) = bucket
+            .iter()
+            .fold((0.0f32, 0.0f32), |(lo, hi), &s| (lo.min(s), hi.max(s)));
+
+        let t = col as f32 / (columns - 1).max(1) as f32;
+        let cr = lerp(left.r as f32, right.r as f32, t) as u8;
+        let cg = lerp(left.g as f32, right.g as f32, t) as u8;
+        let cb = lerp(left.b as f32, right.b as f32, t) as u8;
+
+        let x = PAD_X * s + col as u32;
+        for y in to_y(hi)..=to_y(lo).min(h - 1) {
+            set_pixel_blend(pixels, w, h, x, y, cr, cg, cb, 230);
+        }
+    }
+}
+
+fn render_text_frame(
+    pixels: &mut [u8],
+    w: u32,
+    h: u32,
+    s: u32,
+    lines: &[String],
+    theme: &OsdConfig,
+    fade: f32,
+) {
+    render_background(pixels, w, h, s, theme, fade);
+
+    let color = Color {
+        a: (theme.text.a as f32 * fade) as u8,
+        ..theme.text
+    };
+    for (row, line) in lines.iter().enumerate() {
+        let y = (PAD_Y + row as u32 * TEXT_LINE_HEIGHT + TEXT_SCALE) * s;
+        for (col, ch) in line.chars().enumerate() {
+            let x = (PAD_X + col as u32 * TEXT_ADVANCE) * s;
+            draw_glyph(pixels, w, h, x, y, TEXT_SCALE * s, ch, color);
+        }
+    }
+}
+
+/// Draw `ch` with each font pixel as a `dot` x `dot` square.
+#[allow(clippy::too_many_arguments)]
+fn draw_glyph(
+    pixels: &mut [u8],
+    w: u32,
+    h: u32,
+    x0: u32,
+    y0: u32,
+    dot: u32,
+    ch: char,
+    color: Color,
+) {
+    let Color { r, g, b, a } = color;
+    for (col, bits) in glyph(ch).iter().enumerate() {
+        for row in 0..GLYPH_H {
+            if bits & (1 << row) == 0 {
+                continue;
+            }
+            let px = x0 + col as u32 * dot;
+            let py = y0 + row * dot;
+            for dy in 0..dot {
+                for dx in 0..dot {
+                    set_pixel_blend(pixels, w, h, px + dx, py + dy, r, g, b, a);
+                }
+            }
+        }
+    }
+}
+
+// --- Text layout ---
+
+/// Wrap text into at most `max_lines` lines of `max_cols` characters, keeping
+/// explicit line breaks. Overflow is cut off with "...".
+fn wrap_text(text: &str, max_cols: usize, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            // Hard-split words that can't fit on a line of their own.
+            while word.len() > max_cols {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                lines.push(word.drain(..max_cols).collect());
+            }
+            let line_len = line.chars().count();
+            if line_len > 0 && line_len + 1 + word.len() > max_cols {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.extend(word);
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            let keep = max_cols.saturating_sub(3);
+            if last.chars().count() > keep {
+                *last = last.chars().take(keep).collect();
+            }
+            last.push_str("...");
+        }
+    }
+    lines
+}
+
+/// `text` in characters the built-in font has: Latin-1 letters lose their
+/// accents and typographic quotes and dashes become ASCII. There are no
+/// glyphs for other scripts or emoji, so the text is cut before the first
+/// such character and ends in "..." instead of a run of '?'.
+fn fit_to_font(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        let folded = match ch {
+            ' '..='~' => ch,
+            c if c.is_whitespace() => c,
+            'À'..='Å' => 'A',
+            'à'..='å' => 'a',
+            'Ç' => 'C',
+            'ç' => 'c',
+            'È'..='Ë' => 'E',
+            'è'..='ë' => 'e',
+            'Ì'..='Ï' => 'I',
+            'ì'..='ï' => 'i',
+            'Ñ' => 'N',
+            'ñ' => 'n',
+            'Ò'..='Ö' | 'Ø' => 'O',
+            'ò'..='ö' | 'ø' => 'o',
+            'Ù'..='Ü' => 'U',
+            'ù'..='ü' => 'u',
+            'Ý' => 'Y',
+            'ý' | 'ÿ' => 'y',
+            '‘' | '’' => '\'',
+            '“' | '”' => '"',
+            '–' | '—' => '-',
+            'Æ' | 'æ' | 'ß' | '…' => {
+                out.push_str(match ch {
+                    'Æ' => "AE",
+                    'æ' => "ae",
+                    'ß' => "ss",
+                    _ => "...",
+                });
+                continue;
+            }
+            _ => {
+                out.truncate(out.trim_end().len());
+                out.push_str("...");
+                break;
+            }
+        };
+        out.push(folded);
+    }
+    out
+}
+
+/// 5x7 glyph columns for printable ASCII, bit 0 at the top. Anything else
+/// renders as '?'; `fit_to_font` keeps such characters out of the overlay.
+fn glyph(ch: char) -> &'static [u8; 5] {
+    let index = match ch {
+        ' '..='~' => ch as usize - ' ' as usize,
+        _ => '?' as usize - ' ' as usize,
+    };
+    &FONT_5X7[index]
+}
+
+#[rustfmt::skip]
+const FONT_5X7: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5f, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], // #
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1c, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1c, 0x00], // )
+    [0x14, 0x08, 0x3e, 0x08, 0x14], // *
+    [0x08, 0x08, 0x3e, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], // 0
+    [0x00, 0x42, 0x7f, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4b, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7f, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3c, 0x4a, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1e], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3e], // @
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], // A
+    [0x7f, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3e, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], // D
+    [0x7f, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7f, 0x09, 0x09, 0x09, 0x01], // F
+    [0x3e, 0x41, 0x49, 0x49, 0x7a], // G
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], // H
+    [0x00, 0x41, 0x7f, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3f, 0x01], // J
+    [0x7f, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7f, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f], // M
+    [0x7f, 0x04, 0x08, 0x10, 0x7f], // N
+    [0x3e, 0x41, 0x41, 0x41, 0x3e], // O
+    [0x7f, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3e, 0x41, 0x51, 0x21, 0x5e], // Q
+    [0x7f, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7f, 0x01, 0x01], // T
+    [0x3f, 0x40, 0x40, 0x40, 0x3f], // U
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], // V
+    [0x3f, 0x40, 0x38, 0x40, 0x3f], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x07, 0x08, 0x70, 0x08, 0x07], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7f, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // backslash
+    [0x00, 0x41, 0x41, 0x7f, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7f, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7f], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7e, 0x09, 0x01, 0x02], // f
+    [0x0c, 0x52, 0x52, 0x52, 0x3e], // g
+    [0x7f, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7d, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3d, 0x00], // j
+    [0x7f, 0x10, 0x28, 0x44, 0x00], // k
+    [0x00, 0x41, 0x7f, 0x40, 0x00], // l
+    [0x7c, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7c, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7c, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7c], // q
+    [0x7c, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3f, 0x44, 0x40, 0x20], // t
+    [0x3c, 0x40, 0x40, 0x20, 0x7c], // u
+    [0x1c, 0x20, 0x40, 0x20, 0x1c], // v
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0c, 0x50, 0x50, 0x50, 0x3c], // y
+    [0x44, 0x64, 0x54, 0x4c, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7f, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x10, 0x08, 0x08, 0x10, 0x08], // ~
+];
+
+#[allow(clippy::too_many_arguments)]
 fn present_frame(
     state: &mut OsdState,
     qh: &QueueHandle<OsdState>,
@@ -424,6 +970,7 @@ fn present_frame(
     pixels: &[u8],
     w: u32,
     h: u32,
+    scale: u32,
 ) -> std::io::Result<()> {
     let stride = w * 4;
 
@@ -451,6 +998,7 @@ fn present_frame(
         .surface
         .as_ref()
         .ok_or_else(|| std::io::Error::other("wayland surface not initialized"))?;
+    surface.set_buffer_scale(scale as i32);
     surface.attach(Some(&buffer), 0, 0);
     surface.damage_buffer(0, 0, w as i32, h as i32);
     surface.commit();
@@ -582,6 +1130,33 @@ fn lerp(a: f32, b: f32, t: f32) -> f32 {
     a + (b - a) * t
 }
 
+/// The output named `wanted`, or `None` (the compositor's choice) when it is
+/// empty or no output has that name.
+fn select_output(
+    outputs: &[(wl_output::WlOutput, Option<String>)],
+    wanted: &str,
+) -> Option<wl_output::WlOutput> {
+    if wanted.is_empty() {
+        return None;
+    }
+    let found = outputs
+        .iter()
+        .find(|(_, name)| name.as_deref() == Some(wanted))
+        .map(|(output, _)| output.clone());
+    if found.is_none() {
+        let names: Vec<&str> = outputs.iter().filter_map(|(_, n)| n.as_deref()).collect();
+        eprintln!(
+            "whspr-osd: output '{wanted}' not found (available: {}), using the default output",
+            if names.is_empty() {
+                "none".to_string()
+            } else {
+                names.join(", ")
+            }
+        );
+    }
+    found
+}
+
 // --- Dispatch implementations ---
 
 impl Dispatch<wl_registry::WlRegistry, ()> for OsdState {
@@ -594,7 +1169,9 @@ impl Dispatch<wl_registry::WlRegistry, ()> for OsdState {
         qh: &QueueHandle<Self>,
     ) {
         if let wl_registry::Event::Global {
-            name, interface, ..
+            name,
+            interface,
+            version,
         } = event
         {
             match &interface[..] {
@@ -615,12 +1192,86 @@ impl Dispatch<wl_registry::WlRegistry, ()> for OsdState {
                         ),
                     );
                 }
+                "wl_seat" => {
+                    state.seat =
+                        Some(registry.bind::<wl_seat::WlSeat, _, _>(name, version.min(5), qh, ()));
+                }
+                "wl_output" => {
+                    // Version 4 added the name event
+                    let output =
+                        registry.bind::<wl_output::WlOutput, _, _>(name, version.min(4), qh, ());
+                    state.outputs.push((output, None));
+                }
                 _ => {}
             }
         }
     }
 }
 
+impl Dispatch<wl_output::WlOutput, ()> for OsdState {
+    fn event(
+        state: &mut Self,
+        output: &wl_output::WlOutput,
+        event: wl_output::Event,
+        _: &(),
+        _: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Name { name } = event
+            && let Some(entry) = state.outputs.iter_mut().find(|(o, _)| o == output)
+        {
+            entry.1 = Some(name);
+        }
+    }
+}
+
+impl Dispatch<wl_seat::WlSeat, ()> for OsdState {
+    fn event(
+        state: &mut Self,
+        seat: &wl_seat::WlSeat,
+        event: wl_seat::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_seat::Event::Capabilities {
+            capabilities: WEnum::Value(caps),
+        } = event
+        {
+            let has_pointer = caps.contains(wl_seat::Capability::Pointer);
+            if has_pointer && state.pointer.is_none() {
+                state.pointer = Some(seat.get_pointer(qh, ()));
+            } else if !has_pointer && let Some(pointer) = state.pointer.take() {
+                pointer.release();
+            }
+        }
+    }
+}
+
+impl Dispatch<wl_pointer::WlPointer, ()> for OsdState {
+    fn event(
+        state: &mut Self,
+        _: &wl_pointer::WlPointer,
+        event: wl_pointer::Event,
+        _: &(),
+        _: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // Pointer events only arrive while the pointer is over our surface.
+        if let wl_pointer::Event::Button {
+            button: BTN_LEFT,
+            state: WEnum::Value(wl_pointer::ButtonState::Pressed),
+            ..
+        } = event
+            && state.clickable
+        {
+            // One click is enough; the overlay is killed once recording stops.
+            state.clickable = false;
+            stop_recording();
+        }
+    }
+}
+
 impl Dispatch<zwlr_layer_surface_v1::ZwlrLayerSurfaceV1, ()> for OsdState {
     fn event(
         state: &mut Self,
@@ -654,8 +1305,89 @@ impl Dispatch<zwlr_layer_surface_v1::ZwlrLayerSurfaceV1, ()> for OsdState {
 }
 
 delegate_noop!(OsdState: ignore wl_compositor::WlCompositor);
-delegate_noop!(OsdState: ignore wl_surface::WlSurface);
+
+impl Dispatch<wl_surface::WlSurface, ()> for OsdState {
+    fn event(
+        state: &mut Self,
+        _: &wl_surface::WlSurface,
+        event: wl_surface::Event,
+        _: &(),
+        _: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let wl_surface::Event::PreferredBufferScale { factor } = event {
+            state.scale = factor.max(1) as u32;
+        }
+    }
+}
+
 delegate_noop!(OsdState: ignore wl_shm::WlShm);
 delegate_noop!(OsdState: ignore wl_shm_pool::WlShmPool);
 delegate_noop!(OsdState: ignore wl_buffer::WlBuffer);
 delegate_noop!(OsdState: ignore zwlr_layer_shell_v1::ZwlrLayerShellV1);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_text_wraps_on_word_boundaries() {
+        assert_eq!(
+            wrap_text("the quick brown fox jumps", 10, 5),
+            vec!["the quick", "brown fox", "jumps"]
+        );
+    }
+
+    #[test]
+    fn wrap_text_keeps_explicit_line_breaks() {
+        assert_eq!(wrap_text("first\nsecond", 20, 5), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn wrap_text_splits_long_words_and_truncates_overflow() {
+        assert_eq!(wrap_text("abcdefghij", 4, 5), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(
+            wrap_text("one two three four five six", 9, 2),
+            vec!["one two", "three..."]
+        );
+    }
+
+    #[test]
+    fn sample_ring_snapshot_is_oldest_first() {
+        let ring = SampleRing::new();
+        for i in 0..WAVEFORM_SAMPLES + 3 {
+            ring.push(i as f32);
+        }
+        let mut out = [0.0; WAVEFORM_SAMPLES];
+        ring.snapshot(&mut out);
+        assert_eq!(out[0], 3.0);
+        assert_eq!(out[WAVEFORM_SAMPLES - 1], (WAVEFORM_SAMPLES + 2) as f32);
+    }
+
+    #[test]
+    fn frame_pacer_slows_down_only_after_sustained_quiet() {
+        let mut pacer = FramePacer::new();
+        assert_eq!(pacer.frame_ms(Some(0.2), 0.0), FRAME_MS);
+        assert_eq!(pacer.frame_ms(Some(0.001), 0.5), FRAME_MS);
+        assert_eq!(pacer.frame_ms(Some(0.001), 1.5), QUIET_FRAME_MS);
+        assert_eq!(pacer.frame_ms(Some(0.2), 1.7), FRAME_MS);
+        assert_eq!(pacer.frame_ms(None, 5.0), FRAME_MS);
+    }
+
+    #[test]
+    fn fit_to_font_folds_accents_and_cuts_unknown_scripts() {
+        assert_eq!(
+            fit_to_font("Crème brûlée – “très” bien"),
+            "Creme brulee - \"tres\" bien"
+        );
+        assert_eq!(fit_to_font("Straße\nok"), "Strasse\nok");
+        assert_eq!(fit_to_font("meet at 東京 station"), "meet at...");
+        assert_eq!(fit_to_font("🎉"), "...");
+    }
+
+    #[test]
+    fn glyph_falls_back_for_non_ascii() {
+        assert_eq!(glyph('é'), glyph('?'));
+        assert_eq!(glyph('A'), &[0x7e, 0x11, 0x11, 0x11, 0x7e]);
+    }
+}
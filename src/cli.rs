@@ -2,6 +2,8 @@ use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 
+use crate::output::OutputFormat;
+
 #[derive(Parser, Debug)]
 #[command(
     name = "whspr-rs",
@@ -17,6 +19,16 @@ pub struct Cli {
     #[arg(short, long, action = clap::ArgAction::Count, global = true)]
     pub verbose: u8,
 
+    /// Print the transcript and copy it to the clipboard instead of pasting
+    /// or typing it into the focused window
+    #[arg(long)]
+    pub no_inject: bool,
+
+    /// Never toggle an instance that is already running. If one is, run
+    /// alongside it; stop this one with SIGUSR1/SIGUSR2 to its pid
+    #[arg(long)]
+    pub no_signal: bool,
+
     #[command(subcommand)]
     pub command: Option<Command>,
 }
@@ -26,14 +38,78 @@ pub enum Command {
     /// Interactive first-time setup wizard
     Setup,
 
-    /// Transcribe an audio file (wav, mp3, flac, ogg, mp4/m4a)
+    /// Transcribe an audio file (wav, mp3, flac, ogg vorbis, mp4/m4a,
+    /// webm/mkv). Opus audio, in .opus or WebM files, is not supported
     Transcribe {
-        /// Path to the audio file
-        file: PathBuf,
+        /// Path to the audio file, or - to read it from stdin
+        #[arg(required_unless_present = "list_formats")]
+        file: Option<PathBuf>,
 
         /// Write output to a file instead of stdout
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Write output into this directory, named after --name
+        #[arg(long, conflicts_with = "output")]
+        output_dir: Option<PathBuf>,
+
+        /// Output file name for --output-dir; {stem}, {ext} and {lang} are
+        /// replaced with the input name, format extension and language
+        #[arg(long, default_value = "{stem}.{ext}")]
+        name: String,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Txt)]
+        format: OutputFormat,
+
+        /// Model to use for this run, downloaded if missing (e.g. large-v3)
+        #[arg(short, long)]
+        model: Option<String>,
+
+        /// Skip checksum verification when --model has to download
+        #[arg(long, requires = "model")]
+        no_verify: bool,
+
+        /// Spoken language code, or "auto" (overrides [whisper].language)
+        #[arg(short, long)]
+        language: Option<String>,
+
+        /// Translate speech to English (overrides [whisper].translate)
+        #[arg(long)]
+        translate: bool,
+
+        /// Add per-word timing to JSON output (overrides [whisper].word_timestamps)
+        #[arg(long)]
+        word_timestamps: bool,
+
+        /// Don't show a progress bar while transcribing
+        #[arg(short, long)]
+        quiet: bool,
+
+        /// List the output formats and exit
+        #[arg(long)]
+        list_formats: bool,
+    },
+
+    /// Show whether an instance is running and what it is doing
+    Status,
+
+    /// Discard the recording in progress without transcribing it
+    Cancel,
+
+    /// Terminate a running instance with SIGTERM. A recording is discarded;
+    /// a transcription in progress is finished first
+    Kill,
+
+    /// List available audio input devices
+    Devices,
+
+    /// Check permissions, tools and devices whspr-rs depends on
+    Doctor {
+        /// Also load the model and transcribe a bundled clip, reporting how
+        /// long each step takes
+        #[arg(long)]
+        selftest: bool,
     },
 
     /// Manage whisper models
@@ -41,6 +117,24 @@ pub enum Command {
         #[command(subcommand)]
         action: ModelAction,
     },
+
+    /// Inspect the config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Check the config, model, language, paste keys and audio device
+    Validate,
+
+    /// Print the path of the config file in use
+    Path,
+
+    /// Print the effective config (file merged over defaults)
+    Show,
 }
 
 #[derive(Subcommand, Debug)]
@@ -52,11 +146,37 @@ pub enum ModelAction {
     Download {
         /// Model name (e.g. large-v3-turbo, tiny, base)
         name: String,
+
+        /// Skip checksum verification of the downloaded file
+        #[arg(long)]
+        no_verify: bool,
+
+        /// Resume this many times when the connection drops, waiting longer
+        /// each time
+        #[arg(long, default_value_t = crate::model::DEFAULT_DOWNLOAD_RETRIES)]
+        retries: u32,
     },
 
-    /// Select a downloaded model as active
+    /// Select a downloaded model, or any ggml model file with --path, as active
     Select {
         /// Model name to use
+        #[arg(required_unless_present = "path", conflicts_with = "path")]
+        name: Option<String>,
+
+        /// Path to a custom ggml model file, e.g. a fine-tuned one
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
+    /// Show a model's size, download URL, local path and status
+    Info {
+        /// Model name to describe
+        name: String,
+    },
+
+    /// Delete a downloaded model to free disk space
+    Delete {
+        /// Model name to delete
         name: String,
     },
 }
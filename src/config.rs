@@ -1,43 +1,251 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use crate::error::{Result, WhsprError};
+use crate::osd_config::OsdConfig;
 
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Config {
     pub audio: AudioConfig,
     pub whisper: WhisperConfig,
     pub inject: InjectConfig,
     pub feedback: FeedbackConfig,
+    pub osd: OsdConfig,
+    pub postprocess: PostprocessConfig,
+    pub status: StatusConfig,
+    /// Transcript fixes keyed by the text (or regex) to replace.
+    pub replacements: BTreeMap<String, Replacement>,
+    pub model: ModelConfig,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct AudioConfig {
     pub device: String,
     pub sample_rate: u32,
+    /// Stop recording automatically after this many seconds (0 = no limit).
+    pub max_duration_secs: u64,
+    /// Recordings shorter than this are discarded untranscribed (0 = keep all).
+    pub min_duration_ms: u64,
+    /// Audio discarded right after the stream starts, in milliseconds.
+    pub warmup_ms: u64,
+    /// Scale captured audio toward `target_rms` before transcription.
+    pub normalize: bool,
+    pub target_rms: f32,
+    /// Silence captured blocks whose RMS is below this level (0.0 = off).
+    pub gate_threshold: f32,
+    /// Save each recording as a WAV file here before transcribing it
+    /// (empty = off).
+    pub debug_audio_dir: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct WhisperConfig {
     pub model_path: String,
+    /// Tried in order when `model_path` doesn't exist, e.g. where another
+    /// machine sharing this config keeps its models.
+    pub fallback_model_paths: Vec<String>,
     pub language: String,
     pub use_gpu: bool,
+    /// Retry on the CPU when the model fails to load on the GPU.
+    pub gpu_fallback: bool,
     pub flash_attn: bool,
+    /// Text fed to whisper before each chunk to bias decoding toward
+    /// expected words (empty = none).
+    pub initial_prompt: String,
+    /// Translate speech to English instead of transcribing it verbatim.
+    pub translate: bool,
+    pub sampling: Sampling,
+    /// Number of beams when `sampling = "beam"`.
+    pub beam_size: u32,
+    /// CPU threads for whisper (0 = all available cores).
+    pub n_threads: u32,
+    /// Length of each chunk when splitting long audio, in seconds.
+    pub chunk_secs: f64,
+    /// Overlap between adjacent chunks, in seconds. Must be below `chunk_secs`.
+    pub overlap_secs: f64,
+    /// How many chunks of long audio to transcribe at once (1 = sequential).
+    pub parallel_chunks: u32,
+    /// Compute per-word timing from whisper's token timestamps (slower).
+    pub word_timestamps: bool,
+    /// Segments with a no-speech probability above this are dropped (1.0 = keep all).
+    pub no_speech_threshold: f32,
+    /// How often to print a partial transcript while recording (0 = never).
+    pub partial_interval_secs: f64,
+    /// Don't feed earlier text in a chunk back to whisper as context.
+    pub no_context: bool,
+    /// Force each chunk into a single segment.
+    pub single_segment: bool,
+    /// Decoding temperature; 0.0 is deterministic.
+    pub temperature: f32,
+    /// Temperature step when decoding fails the thresholds below (0.0 = no
+    /// fallback).
+    pub temperature_inc: f32,
+    /// Retry at a higher temperature when a segment's token entropy is above
+    /// this, a sign of repetition loops.
+    pub entropy_threshold: f32,
+    /// Retry at a higher temperature when a segment's average log
+    /// probability is below this.
+    pub logprob_threshold: f32,
 }
 
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sampling {
+    /// Take the most likely token at each step
+    #[default]
+    Greedy,
+    /// Keep several candidate sequences; slower but more accurate on noisy audio
+    Beam,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
-pub struct InjectConfig {}
+pub struct InjectConfig {
+    pub backend: InjectBackendKind,
+    pub mode: InjectMode,
+    /// Keys pressed to paste, e.g. `["LEFTCTRL", "V"]`. All but the last are
+    /// held while the last is tapped.
+    pub paste_keys: Vec<String>,
+    /// Which Wayland selection(s) the text is copied to.
+    pub selection: Selection,
+    /// Wait between setting the clipboard and pressing `paste_keys`, in
+    /// milliseconds.
+    pub clipboard_delay_ms: u64,
+    /// Stay busy this long after injecting; a key press during a dictation
+    /// or the cooldown then queues the next one (0 = off).
+    pub cooldown_ms: u64,
+    /// Focused windows whose app_id/class is listed here never get text
+    /// injected, e.g. password prompts. Matched case-insensitively.
+    pub deny_apps: Vec<String>,
+    /// How to look up the focused window for `deny_apps`.
+    pub window_query: WindowQueryKind,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InjectBackendKind {
+    /// wl-copy for the clipboard plus a uinput virtual keyboard
+    #[default]
+    Uinput,
+    /// `ydotool type` via a running ydotoold
+    Ydotool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InjectMode {
+    /// Copy to the clipboard and send the paste keystroke
+    #[default]
+    Paste,
+    /// Copy to the clipboard only; the user pastes manually
+    Clipboard,
+    /// Type each character with the virtual keyboard, bypassing the clipboard
+    Type,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowQueryKind {
+    /// Pick the compositor from the environment
+    #[default]
+    Auto,
+    /// `hyprctl activewindow -j`
+    Hyprland,
+    /// `swaymsg -t get_tree`
+    Sway,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Selection {
+    /// The regular clipboard, pasted with Ctrl+V and friends
+    #[default]
+    Clipboard,
+    /// The primary selection, pasted with middle-click
+    Primary,
+    /// Both of them
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SoundTheme {
+    /// Soft chimes
+    #[default]
+    Chime,
+    /// Plain sine beeps
+    Beep,
+    /// Short, quiet clicks
+    Click,
+}
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct FeedbackConfig {
     pub enabled: bool,
+    /// Bundled sound set used for every sound without a custom path.
+    pub theme: SoundTheme,
     pub start_sound: String,
     pub stop_sound: String,
+    /// Played once transcription succeeds, just before the text is injected.
+    pub complete_sound: String,
+    /// Played when transcription or injection fails, or nothing was heard.
+    pub error_sound: String,
+    /// Played when a recording is cancelled and discarded.
+    pub cancel_sound: String,
+    /// Playback volume from 0.0 (silent) to 1.0 (as recorded).
+    pub volume: f32,
+    /// Output device name substring for the sounds (empty = system default).
+    pub device: String,
+}
+
+/// Cleanups applied to the transcript right before injection.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PostprocessConfig {
+    /// Uppercase the first letter of the transcript.
+    pub capitalize_first: bool,
+    /// Append a space so consecutive dictations don't run together.
+    pub trailing_space: bool,
+    /// Transcripts that are discarded instead of injected when they make up
+    /// the whole dictation, compared ignoring case and punctuation.
+    pub blocklist: Vec<String>,
+}
+
+/// State reporting for status bars.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StatusConfig {
+    /// Stream state changes over `$XDG_RUNTIME_DIR/whspr-rs.sock`.
+    pub socket: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Replacement {
+    /// `"get hub" = "GitHub"`: replace the literal text
+    Literal(String),
+    /// `'\bteh\b' = { with = "the", regex = true }`
+    Rule {
+        with: String,
+        #[serde(default)]
+        regex: bool,
+    },
+}
+
+/// Default `[model].base_url`: the whisper.cpp models on Hugging Face.
+pub const DEFAULT_MODEL_URL: &str =
+    "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/{filename}";
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ModelConfig {
+    /// Download URL template; `{filename}` is replaced with the model file name.
+    pub base_url: String,
 }
 
 impl Default for AudioConfig {
@@ -45,6 +253,13 @@ impl Default for AudioConfig {
         Self {
             device: String::new(),
             sample_rate: 16000,
+            max_duration_secs: 0,
+            min_duration_ms: 300,
+            warmup_ms: 0,
+            normalize: false,
+            target_rms: 0.05,
+            gate_threshold: 0.0,
+            debug_audio_dir: String::new(),
         }
     }
 }
@@ -53,9 +268,43 @@ impl Default for WhisperConfig {
     fn default() -> Self {
         Self {
             model_path: "~/.local/share/whspr-rs/ggml-large-v3-turbo.bin".into(),
+            fallback_model_paths: Vec::new(),
             language: "auto".into(),
             use_gpu: true,
+            gpu_fallback: true,
             flash_attn: true,
+            initial_prompt: String::new(),
+            translate: false,
+            sampling: Sampling::default(),
+            beam_size: 5,
+            n_threads: 0,
+            chunk_secs: 30.0,
+            overlap_secs: 1.0,
+            parallel_chunks: 1,
+            word_timestamps: false,
+            no_speech_threshold: 1.0,
+            partial_interval_secs: 0.0,
+            no_context: true,
+            single_segment: false,
+            temperature: 0.0,
+            temperature_inc: 0.2,
+            entropy_threshold: 2.4,
+            logprob_threshold: -1.0,
+        }
+    }
+}
+
+impl Default for InjectConfig {
+    fn default() -> Self {
+        Self {
+            backend: InjectBackendKind::default(),
+            mode: InjectMode::default(),
+            paste_keys: vec!["LEFTCTRL".into(), "LEFTSHIFT".into(), "V".into()],
+            selection: Selection::default(),
+            clipboard_delay_ms: 180,
+            cooldown_ms: 0,
+            deny_apps: Vec::new(),
+            window_query: WindowQueryKind::default(),
         }
     }
 }
@@ -64,8 +313,22 @@ impl Default for FeedbackConfig {
     fn default() -> Self {
         Self {
             enabled: true,
+            theme: SoundTheme::default(),
             start_sound: String::new(),
             stop_sound: String::new(),
+            complete_sound: String::new(),
+            error_sound: String::new(),
+            cancel_sound: String::new(),
+            volume: 1.0,
+            device: String::new(),
+        }
+    }
+}
+
+impl Default for ModelConfig {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_MODEL_URL.into(),
         }
     }
 }
@@ -86,18 +349,164 @@ impl Config {
             WhsprError::Config(format!("failed to read {}: {e}", config_path.display()))
         })?;
 
+        // Warn first: a misspelt key is often why the file then fails to
+        // parse or validate.
+        for key in unknown_keys(&contents) {
+            tracing::warn!("{}: unknown key `{key}` is ignored", config_path.display());
+        }
         let config: Config = toml::from_str(&contents).map_err(|e| {
             WhsprError::Config(format!("failed to parse {}: {e}", config_path.display()))
         })?;
+        config.validate()?;
 
         Ok(config)
     }
 
+    /// Let `WHSPR_MODEL_PATH`, `WHSPR_LANGUAGE` and `WHSPR_AUDIO_DEVICE`
+    /// override the file, e.g. in containers or tests. The result is
+    /// validated again.
+    pub fn apply_env_overrides(&mut self) -> Result<()> {
+        let overrides = [
+            ("WHSPR_MODEL_PATH", &mut self.whisper.model_path),
+            ("WHSPR_LANGUAGE", &mut self.whisper.language),
+            ("WHSPR_AUDIO_DEVICE", &mut self.audio.device),
+        ];
+        for (var, field) in overrides {
+            if let Ok(value) = std::env::var(var) {
+                tracing::debug!("{var} overrides config: {value:?}");
+                *field = value;
+            }
+        }
+        self.validate()
+    }
+
+    fn validate(&self) -> Result<()> {
+        let whisper = &self.whisper;
+        crate::transcribe::check_language(&whisper.language)?;
+        if whisper.chunk_secs.is_nan() || whisper.chunk_secs < 1.0 {
+            return Err(WhsprError::Config(format!(
+                "[whisper].chunk_secs must be at least 1 second, got {}",
+                whisper.chunk_secs
+            )));
+        }
+        if !(0.0..whisper.chunk_secs).contains(&whisper.overlap_secs) {
+            return Err(WhsprError::Config(format!(
+                "[whisper].overlap_secs must be at least 0 and less than chunk_secs ({}), got {}",
+                whisper.chunk_secs, whisper.overlap_secs
+            )));
+        }
+        if !(0.0..=1.0).contains(&whisper.no_speech_threshold) {
+            return Err(WhsprError::Config(format!(
+                "[whisper].no_speech_threshold must be between 0.0 and 1.0, got {}",
+                whisper.no_speech_threshold
+            )));
+        }
+        for (name, value) in [
+            ("temperature", whisper.temperature),
+            ("temperature_inc", whisper.temperature_inc),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(WhsprError::Config(format!(
+                    "[whisper].{name} must be between 0.0 and 1.0, got {value}"
+                )));
+            }
+        }
+        if whisper.entropy_threshold.is_nan() || whisper.entropy_threshold <= 0.0 {
+            return Err(WhsprError::Config(format!(
+                "[whisper].entropy_threshold must be above 0, got {}",
+                whisper.entropy_threshold
+            )));
+        }
+        if whisper.logprob_threshold.is_nan() || whisper.logprob_threshold > 0.0 {
+            return Err(WhsprError::Config(format!(
+                "[whisper].logprob_threshold must be 0 or below, got {}",
+                whisper.logprob_threshold
+            )));
+        }
+        let partial = whisper.partial_interval_secs;
+        if partial.is_nan() || (partial != 0.0 && partial < 1.0) {
+            return Err(WhsprError::Config(format!(
+                "[whisper].partial_interval_secs must be 0 (off) or at least 1 second, got {partial}"
+            )));
+        }
+        if self.inject.backend == InjectBackendKind::Ydotool && self.inject.mode != InjectMode::Type
+        {
+            return Err(WhsprError::Config(
+                "[inject].backend = \"ydotool\" can only type text; set [inject].mode = \"type\" \
+                 or use backend = \"uinput\""
+                    .into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The config in the same TOML layout as the file.
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self)
+            .map_err(|e| WhsprError::Config(format!("failed to serialize config: {e}")))
+    }
+
+    /// `model_path`, or the first of `fallback_model_paths` that exists if it
+    /// doesn't. When none exist it is still `model_path`, so errors name that.
     pub fn resolved_model_path(&self) -> PathBuf {
-        PathBuf::from(expand_tilde(&self.whisper.model_path))
+        let primary = PathBuf::from(expand_tilde(&self.whisper.model_path));
+        if primary.exists() {
+            return primary;
+        }
+        let fallback = self
+            .whisper
+            .fallback_model_paths
+            .iter()
+            .map(|path| PathBuf::from(expand_tilde(path)))
+            .find(|path| path.exists());
+        match fallback {
+            Some(path) => {
+                tracing::info!(
+                    "model {} not found, using {}",
+                    primary.display(),
+                    path.display()
+                );
+                path
+            }
+            None => primary,
+        }
     }
 }
 
+/// Keys in a config file that no setting reads, e.g. a typo like
+/// `audio.sampl_rate`. Every setting serializes in `Config::default()`, so
+/// that doubles as the list of known keys. `[replacements]` is free-form.
+pub fn unknown_keys(contents: &str) -> Vec<String> {
+    let Ok(file) = toml::from_str::<toml::Table>(contents) else {
+        return Vec::new();
+    };
+    let known = Config::default()
+        .to_toml()
+        .ok()
+        .and_then(|s| toml::from_str::<toml::Table>(&s).ok())
+        .unwrap_or_default();
+
+    let mut unknown = Vec::new();
+    for (section, value) in &file {
+        let Some(known_section) = known.get(section) else {
+            unknown.push(section.clone());
+            continue;
+        };
+        if section == "replacements" {
+            continue;
+        }
+        if let (Some(table), Some(known_table)) = (value.as_table(), known_section.as_table()) {
+            unknown.extend(
+                table
+                    .keys()
+                    .filter(|key| !known_table.contains_key(*key))
+                    .map(|key| format!("{section}.{key}")),
+            );
+        }
+    }
+    unknown
+}
+
 pub fn default_config_path() -> PathBuf {
     xdg_dir("config").join("whspr-rs").join("config.toml")
 }
@@ -113,6 +522,13 @@ pub fn data_dir() -> PathBuf {
     xdg_dir("data").join("whspr-rs")
 }
 
+/// `$XDG_RUNTIME_DIR`, where the compositor and daemons put their sockets.
+/// whspr-rs's own files go through `runtime::runtime_file`.
+pub fn runtime_dir() -> PathBuf {
+    let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".into());
+    PathBuf::from(runtime_dir)
+}
+
 fn xdg_dir(kind: &str) -> PathBuf {
     match kind {
         "config" => {
@@ -163,7 +579,7 @@ pub fn expand_tilde(path: &str) -> String {
 
 pub fn write_default_config(path: &Path, model_path: &str) -> Result<()> {
     let contents = format!(
-        r#"# whspr-rs configuration
+        r##"# whspr-rs configuration
 #
 # Keybinding is handled by your compositor. Example for Hyprland:
 #   bind = SUPER ALT, D, exec, whspr-rs
@@ -171,29 +587,196 @@ pub fn write_default_config(path: &Path, model_path: &str) -> Result<()> {
 # First invocation starts recording, second invocation stops + transcribes + pastes.
 
 [audio]
-# Input device name (empty = system default)
+# Input device name substring, or "index:N" for the Nth device listed by
+# `whspr-rs devices` (empty = system default)
 device = ""
 # Sample rate in Hz (whisper requires 16000)
 sample_rate = 16000
+# Stop recording automatically after this many seconds (0 = no limit)
+max_duration_secs = 0
+# Discard recordings shorter than this, e.g. from an accidental double tap,
+# instead of transcribing them (0 = keep everything)
+min_duration_ms = 300
+# Drop this much audio right after recording starts, e.g. 50 if the tail of
+# the start sound or mic ramp-up turns into a spurious first word
+warmup_ms = 0
+# Boost quiet recordings toward target_rms before transcription (useful for
+# quiet mics; gain is capped so silence isn't amplified into noise)
+normalize = false
+target_rms = 0.05
+# Noise gate: silence input quieter than this RMS level, e.g. 0.005 to cut
+# fan hum (0.0 = disabled)
+gate_threshold = 0.0
+# Save every recording, exactly as whisper gets it, as a WAV file in this
+# directory; handy for reporting misrecognitions (empty = off)
+debug_audio_dir = ""
 
 [whisper]
 # Path to ggml whisper model file
 # Manage models with: whspr-rs model list / download / select
 model_path = "{model_path}"
+# Other model files to try, in order, when model_path doesn't exist, e.g.
+# where another machine sharing this config keeps its models
+fallback_model_paths = []
 # Language code ("en", "fr", "de", etc.) or "auto" for auto-detect
 language = "auto"
 # Enable GPU acceleration (set false to force CPU)
 use_gpu = true
+# If the model doesn't fit on the GPU, load it on the CPU instead of failing
+gpu_fallback = true
 # Enable flash attention when GPU is enabled
 flash_attn = true
+# Words to bias recognition toward, e.g. names and jargon the model mangles.
+# Works best as a short comma-separated list of proper nouns (empty = none).
+initial_prompt = ""
+# Translate non-English speech to English text instead of transcribing it
+translate = false
+# Decoding strategy: "greedy" is fastest; "beam" is more accurate on noisy
+# audio but roughly beam_size times slower
+sampling = "greedy"
+beam_size = 5
+# CPU threads for whisper (0 = all cores); lower it to keep the desktop
+# responsive while long files transcribe
+n_threads = 0
+# Long audio is split into chunks of chunk_secs that overlap by overlap_secs;
+# longer overlap reduces artifacts at chunk boundaries
+chunk_secs = 30.0
+overlap_secs = 1.0
+# Transcribe this many chunks of long audio at once. Each one loads its own
+# whisper state (hundreds of MB for large models), and n_threads is split
+# between them
+parallel_chunks = 1
+# Per-word start/end times in `whspr-rs transcribe --format json` output.
+# Makes whisper compute token timestamps, which costs extra processing time
+word_timestamps = false
+# Drop segments whisper thinks are likely silence (no-speech probability above
+# this), e.g. a "Thanks for watching" after you stop talking. 1.0 keeps
+# everything; 0.6 is a reasonable starting point
+no_speech_threshold = 1.0
+# While recording, transcribe the most recent chunk_secs of audio this often
+# and print it to stderr, so long dictations show text as you speak. Each pass
+# costs a full transcription of that window; 0 turns it off
+partial_interval_secs = 0.0
+# Decode each stretch of speech without the text before it as context. Context
+# helps long, continuous speech but can carry a mistake forward
+no_context = true
+# Produce one segment per chunk. Speeds up short dictations; keep it off for
+# long files, where it merges everything and loses timestamps
+single_segment = false
+# Decoding temperature: 0.0 always picks the most likely text. When a segment
+# looks bad (token entropy above entropy_threshold, e.g. a repetition loop, or
+# average log probability below logprob_threshold), whisper decodes it again
+# with the temperature raised by temperature_inc, up to 1.0. Set
+# temperature_inc = 0.0 to turn that fallback off
+temperature = 0.0
+temperature_inc = 0.2
+entropy_threshold = 2.4
+logprob_threshold = -1.0
+
+[inject]
+# "uinput" (wl-copy + virtual keyboard) or "ydotool" (types via a running
+# ydotoold; needs mode = "type")
+backend = "uinput"
+# "paste" copies to the clipboard and sends Ctrl+Shift+V
+# "clipboard" only copies; paste it yourself (no uinput access needed)
+# "type" types the text key by key (US layout, ASCII only) for apps without paste
+mode = "paste"
+# Keys sent to paste in "paste" mode; use ["LEFTCTRL", "V"] for apps that
+# don't accept Ctrl+Shift+V
+paste_keys = ["LEFTCTRL", "LEFTSHIFT", "V"]
+# Where the text is copied: "clipboard", "primary" (middle-click paste, e.g.
+# in XWayland apps and terminals) or "both". With "primary" no paste
+# keystroke is sent, since paste keys read the clipboard
+selection = "clipboard"
+# How long to give the compositor to take the clipboard before pasting;
+# raise it (e.g. 400) if a slow compositor pastes the previous clipboard
+clipboard_delay_ms = 180
+# Wait this long after pasting before another dictation can start. While
+# it is set, pressing the key again before a dictation has been pasted
+# records the next one right after, instead of being ignored; e.g. 300 when
+# quick successive pastes land in the wrong order (0 = off)
+cooldown_ms = 0
+# Never inject into windows with one of these app_ids/X11 classes, e.g.
+# ["pinentry-qt", "org.keepassxc.KeePassXC"]; the text is dropped instead.
+# Matched ignoring case. `hyprctl activewindow` or `swaymsg -t get_tree`
+# show a window's app_id/class
+deny_apps = []
+# How to find the focused window for deny_apps: "auto", "hyprland" or "sway"
+window_query = "auto"
 
 [feedback]
 # Play sound feedback on start/stop
 enabled = true
-# Custom sound file paths (empty = use bundled sounds)
+# Bundled sounds: "chime", "beep" or "click"
+theme = "chime"
+# Custom sound file paths, overriding the theme (empty = use bundled sounds)
 start_sound = ""
 stop_sound = ""
-"#
+# Played when transcription finishes, right before the text appears
+complete_sound = ""
+# Played when transcription or injection fails
+error_sound = ""
+# Played when a recording is cancelled with `whspr-rs cancel`
+cancel_sound = ""
+# Feedback volume, 0.0 to 1.0
+volume = 1.0
+# Play the sounds on this output device (name substring), e.g. speakers while
+# the mic is a headset; empty = system default
+device = ""
+
+[osd]
+# The overlay's font only covers ASCII and accented Latin letters; a
+# transcript in another script is cut off with "..." where that script starts.
+# Launch the overlay while recording; false if you run whspr-osd yourself
+# (it then reads this section from the default config file)
+autostart = true
+# Overlay colors as "#rrggbb" or "#rrggbbaa"
+background = "#12121eb9"
+border = "#8cb4ff28"
+bar_left = "#00d1bfff"
+bar_right = "#a659ffff"
+text = "#ebeefaff"
+# Screen edge to attach to: "top", "bottom", "left" or "right"
+anchor = "bottom"
+# Distance from that edge in pixels
+margin = 40
+# Recording visualizer: "bars" or "waveform"
+style = "bars"
+# Monitor to show the overlay on, by connector name such as "DP-1" (see
+# `hyprctl monitors` or `swaymsg -t get_outputs`); empty = compositor's choice
+output = ""
+
+[postprocess]
+# Uppercase the first letter of each dictation
+capitalize_first = false
+# Append a space after each dictation so the next one doesn't run into it
+trailing_space = false
+# A dictation that is nothing but one of these phrases (ignoring case and
+# punctuation) is dropped instead of pasted. Whisper sometimes invents video
+# outros such as "Thanks for watching!" from near-silent clips
+blocklist = []
+
+[status]
+# Stream state changes (recording, transcribing, injecting, idle) as lines
+# on $XDG_RUNTIME_DIR/whspr-rs.sock for status bars
+socket = false
+
+[replacements]
+# Fix words whisper keeps getting wrong. Keys are matched literally and
+# case-sensitively; add regex = true for a regular expression (use (?i) to
+# ignore case). Rules run in alphabetical order of their keys, not file
+# order, and each one sees the text the rules before it produced.
+# "get hub" = "GitHub"
+# "pie torch" = "PyTorch"
+# '(?i)\bwhisper rs\b' = {{ with = "whspr-rs", regex = true }}
+
+[model]
+# Download URL for `whspr-rs model download`; {{filename}} is replaced with the
+# model file name (e.g. ggml-large-v3-turbo.bin). Point this at a mirror if
+# Hugging Face is unreachable.
+base_url = "{model_url}"
+"##,
+        model_url = DEFAULT_MODEL_URL
     );
 
     if let Some(parent) = path.parent() {
@@ -249,6 +832,245 @@ mod tests {
         }
     }
 
+    #[test]
+    fn load_parses_inject_mode() {
+        let path = crate::test_support::unique_temp_path("config-inject-mode", "toml");
+        std::fs::write(&path, "[inject]\nmode = \"clipboard\"\n").expect("write config");
+        let config = Config::load(Some(&path)).expect("load config");
+        assert_eq!(config.inject.mode, InjectMode::Clipboard);
+    }
+
+    #[test]
+    fn load_parses_beam_sampling() {
+        let path = crate::test_support::unique_temp_path("config-sampling", "toml");
+        std::fs::write(&path, "[whisper]\nsampling = \"beam\"\nbeam_size = 3\n")
+            .expect("write config");
+        let config = Config::load(Some(&path)).expect("load config");
+        assert_eq!(config.whisper.sampling, Sampling::Beam);
+        assert_eq!(config.whisper.beam_size, 3);
+    }
+
+    #[test]
+    fn load_parses_literal_and_regex_replacements() {
+        let path = crate::test_support::unique_temp_path("config-replacements", "toml");
+        std::fs::write(
+            &path,
+            "[replacements]\n\"get hub\" = \"GitHub\"\n'\\bteh\\b' = { with = \"the\", regex = true }\n",
+        )
+        .expect("write config");
+        let config = Config::load(Some(&path)).expect("load config");
+        assert_eq!(
+            config.replacements.get("get hub"),
+            Some(&Replacement::Literal("GitHub".into()))
+        );
+        assert_eq!(
+            config.replacements.get("\\bteh\\b"),
+            Some(&Replacement::Rule {
+                with: "the".into(),
+                regex: true
+            })
+        );
+    }
+
+    #[test]
+    fn load_rejects_overlap_not_below_chunk() {
+        let path = crate::test_support::unique_temp_path("config-overlap", "toml");
+        std::fs::write(&path, "[whisper]\nchunk_secs = 10.0\noverlap_secs = 10.0\n")
+            .expect("write config");
+        let err = Config::load(Some(&path)).expect_err("overlap == chunk should fail");
+        match err {
+            WhsprError::Config(msg) => {
+                assert!(msg.contains("overlap_secs"), "unexpected message: {msg}");
+            }
+            other => panic!("unexpected error variant: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn load_rejects_ydotool_without_type_mode() {
+        let path = crate::test_support::unique_temp_path("config-ydotool", "toml");
+        std::fs::write(
+            &path,
+            "[inject]\nbackend = \"ydotool\"\nmode = \"clipboard\"\n",
+        )
+        .expect("write config");
+        let err = Config::load(Some(&path)).expect_err("ydotool with clipboard mode should fail");
+        match err {
+            WhsprError::Config(msg) => {
+                assert!(msg.contains("ydotool"), "unexpected message: {msg}");
+            }
+            other => panic!("unexpected error variant: {other:?}"),
+        }
+
+        std::fs::write(&path, "[inject]\nbackend = \"ydotool\"\nmode = \"type\"\n")
+            .expect("write config");
+        Config::load(Some(&path)).expect("ydotool with type mode should load");
+    }
+
+    #[test]
+    fn load_rejects_no_speech_threshold_out_of_range() {
+        let path = crate::test_support::unique_temp_path("config-no-speech", "toml");
+        std::fs::write(&path, "[whisper]\nno_speech_threshold = 1.5\n").expect("write config");
+        let err = Config::load(Some(&path)).expect_err("threshold above 1 should fail");
+        match err {
+            WhsprError::Config(msg) => {
+                assert!(
+                    msg.contains("no_speech_threshold"),
+                    "unexpected message: {msg}"
+                );
+            }
+            other => panic!("unexpected error variant: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn load_rejects_decoding_thresholds_out_of_range() {
+        for (setting, key) in [
+            ("temperature = 1.5", "temperature"),
+            ("temperature_inc = -0.2", "temperature_inc"),
+            ("entropy_threshold = 0.0", "entropy_threshold"),
+            ("logprob_threshold = 0.5", "logprob_threshold"),
+        ] {
+            let path = crate::test_support::unique_temp_path("config-decoding", "toml");
+            std::fs::write(&path, format!("[whisper]\n{setting}\n")).expect("write config");
+            let err = Config::load(Some(&path)).expect_err("out-of-range value should fail");
+            match err {
+                WhsprError::Config(msg) => {
+                    assert!(msg.contains(key), "unexpected message: {msg}");
+                }
+                other => panic!("unexpected error variant: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn resolved_model_path_falls_back_to_first_existing_candidate() {
+        let dir = crate::test_support::unique_temp_dir("config-fallback-model");
+        let present = dir.join("ggml-tiny.bin");
+        std::fs::write(&present, b"model").expect("write model");
+        let missing = dir.join("ggml-large-v3.bin");
+
+        let mut config = Config::default();
+        config.whisper.model_path = missing.display().to_string();
+        assert_eq!(config.resolved_model_path(), missing);
+
+        config.whisper.fallback_model_paths = vec![
+            dir.join("ggml-base.bin").display().to_string(),
+            present.display().to_string(),
+        ];
+        assert_eq!(config.resolved_model_path(), present);
+
+        std::fs::write(&missing, b"model").expect("write primary model");
+        assert_eq!(config.resolved_model_path(), missing);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn to_toml_roundtrips_through_load() {
+        let path = crate::test_support::unique_temp_path("config-roundtrip", "toml");
+        std::fs::write(
+            &path,
+            r#"
+[whisper]
+sampling = "beam"
+language = "de"
+
+[osd]
+anchor = "top"
+
+[replacements]
+"get hub" = "GitHub"
+'\bteh\b' = { with = "the", regex = true }
+"#,
+        )
+        .expect("write config");
+        let config = Config::load(Some(&path)).expect("load config");
+        let encoded = config.to_toml().expect("serialize config");
+
+        std::fs::write(&path, &encoded).expect("write serialized config");
+        let reloaded = Config::load(Some(&path)).expect("reload serialized config");
+        assert_eq!(reloaded.to_toml().expect("serialize again"), encoded);
+        assert_eq!(reloaded.whisper.sampling, Sampling::Beam);
+        assert_eq!(reloaded.whisper.language, "de");
+        assert_eq!(
+            reloaded.replacements.get("get hub"),
+            Some(&Replacement::Literal("GitHub".into()))
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn to_toml_matches_documented_layout() {
+        let documented: toml::Table =
+            toml::from_str(include_str!("../config.example.toml")).expect("parse example");
+        let serialized: toml::Table =
+            toml::from_str(&Config::default().to_toml().expect("serialize defaults"))
+                .expect("parse serialized defaults");
+
+        let sections = |table: &toml::Table| table.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(sections(&serialized), sections(&documented));
+        for (name, section) in &documented {
+            if name == "replacements" {
+                continue;
+            }
+            let keys = |value: &toml::Value| {
+                let mut keys: Vec<_> = value.as_table().expect("section").keys().cloned().collect();
+                keys.sort();
+                keys
+            };
+            assert_eq!(keys(&serialized[name]), keys(section), "[{name}]");
+        }
+    }
+
+    #[test]
+    fn unknown_keys_reports_typos_but_not_replacements() {
+        let contents = r#"
+typo_section = 1
+
+[audio]
+sampl_rate = 16000
+device = ""
+
+[replacements]
+"anything goes" = "here"
+"#;
+        assert_eq!(
+            unknown_keys(contents),
+            vec!["audio.sampl_rate".to_string(), "typo_section".to_string()]
+        );
+        assert!(unknown_keys(include_str!("../config.example.toml")).is_empty());
+    }
+
+    #[test]
+    fn load_rejects_unknown_language() {
+        let path = crate::test_support::unique_temp_path("config-language", "toml");
+        std::fs::write(&path, "[whisper]\nlanguage = \"eng\"\n").expect("write config");
+        let err = Config::load(Some(&path)).expect_err("unknown language should fail");
+        assert!(err.to_string().contains("'eng'"));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn apply_env_overrides_replaces_only_set_fields() {
+        let _env_lock = crate::test_support::env_lock();
+        let _guard = crate::test_support::EnvVarGuard::capture(&[
+            "WHSPR_MODEL_PATH",
+            "WHSPR_LANGUAGE",
+            "WHSPR_AUDIO_DEVICE",
+        ]);
+        crate::test_support::set_env("WHSPR_MODEL_PATH", "/models/ggml-tiny.bin");
+        crate::test_support::set_env("WHSPR_AUDIO_DEVICE", "");
+        crate::test_support::remove_env("WHSPR_LANGUAGE");
+
+        let mut config = Config::default();
+        config.audio.device = "USB".into();
+        config.apply_env_overrides().expect("overrides are valid");
+
+        assert_eq!(config.whisper.model_path, "/models/ggml-tiny.bin");
+        assert_eq!(config.whisper.language, "auto");
+        assert_eq!(config.audio.device, "");
+    }
+
     #[test]
     fn expand_tilde_uses_home_when_present() {
         let _env_lock = crate::test_support::env_lock();
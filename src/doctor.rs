@@ -0,0 +1,330 @@
+use std::path::{Path, PathBuf};
+
+use std::time::Instant;
+
+use whspr_rs::audio;
+use whspr_rs::config::{self, Config, InjectBackendKind, InjectMode};
+use whspr_rs::error::{Result, WhsprError};
+use whspr_rs::file_audio;
+use whspr_rs::transcribe::{self, WhisperLocal};
+
+use crate::validate;
+
+enum Level {
+    Pass,
+    Warn,
+    Fail,
+}
+
+struct Check {
+    level: Level,
+    label: String,
+    /// What to do about a warning or failure.
+    hint: Option<String>,
+}
+
+impl Check {
+    fn pass(label: impl Into<String>) -> Self {
+        Self {
+            level: Level::Pass,
+            label: label.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(label: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            level: Level::Warn,
+            label: label.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(label: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            level: Level::Fail,
+            label: label.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Check the system for what whspr-rs needs at runtime, with a fix for each
+/// problem found. `selftest` adds a real model load and transcription.
+pub fn run_doctor(config_path: Option<&Path>, selftest: bool) -> Result<()> {
+    let mut config = match Config::load(config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("  FAIL  config: {e}");
+            println!("        fix it or run `whspr-rs config validate` for details");
+            return Err(WhsprError::Config("config is invalid".into()));
+        }
+    };
+    config.apply_env_overrides()?;
+
+    let checks = [
+        check_wayland(),
+        check_clipboard(&config),
+        check_uinput(&config),
+        check_ydotool(&config),
+        check_osd(&config),
+        check_model(&config),
+        check_audio(&config),
+        selftest.then(|| check_selftest(&config)).flatten(),
+    ];
+
+    let mut failed = 0;
+    for check in checks.into_iter().flatten() {
+        let tag = match check.level {
+            Level::Pass => "ok  ",
+            Level::Warn => "warn",
+            Level::Fail => {
+                failed += 1;
+                "FAIL"
+            }
+        };
+        println!("  {tag}  {}", check.label);
+        if let Some(hint) = check.hint {
+            println!("        {hint}");
+        }
+    }
+
+    if failed > 0 {
+        return Err(WhsprError::Config(format!("{failed} check(s) failed")));
+    }
+    println!("everything whspr-rs needs is in place");
+    Ok(())
+}
+
+fn check_wayland() -> Option<Check> {
+    let Ok(display) = std::env::var("WAYLAND_DISPLAY") else {
+        return Some(Check::fail(
+            "Wayland session",
+            "WAYLAND_DISPLAY is not set; run whspr-rs from your Wayland session",
+        ));
+    };
+    let socket = if Path::new(&display).is_absolute() {
+        PathBuf::from(&display)
+    } else {
+        config::runtime_dir().join(&display)
+    };
+    Some(if socket.exists() {
+        Check::pass(format!("Wayland socket {}", socket.display()))
+    } else {
+        Check::fail(
+            format!("Wayland socket {}", socket.display()),
+            "the compositor socket is missing; is XDG_RUNTIME_DIR correct?",
+        )
+    })
+}
+
+fn check_clipboard(config: &Config) -> Option<Check> {
+    if config.inject.backend != InjectBackendKind::Uinput {
+        return None;
+    }
+    Some(match find_in_path("wl-copy") {
+        Some(path) => Check::pass(format!("wl-copy at {}", path.display())),
+        None => Check::fail(
+            "wl-copy",
+            "not found on PATH; install wl-clipboard from your distribution",
+        ),
+    })
+}
+
+fn check_uinput(config: &Config) -> Option<Check> {
+    // Clipboard-only mode and the ydotool backend never open uinput.
+    if config.inject.backend != InjectBackendKind::Uinput
+        || config.inject.mode == InjectMode::Clipboard
+    {
+        return None;
+    }
+    let label = "/dev/uinput writable";
+    Some(
+        match std::fs::OpenOptions::new().write(true).open("/dev/uinput") {
+            Ok(_) => Check::pass(label),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Check::fail(
+                label,
+                "the uinput module isn't loaded; run `sudo modprobe uinput`",
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => Check::fail(
+                label,
+                "run `sudo usermod -aG input $USER`, then log out and back in",
+            ),
+            Err(e) => Check::fail(label, format!("cannot open it: {e}")),
+        },
+    )
+}
+
+fn check_ydotool(config: &Config) -> Option<Check> {
+    if config.inject.backend != InjectBackendKind::Ydotool {
+        return None;
+    }
+    let Some(path) = find_in_path("ydotool") else {
+        return Some(Check::fail(
+            "ydotool",
+            "not found on PATH; install ydotool and start ydotoold",
+        ));
+    };
+    let label = format!("ydotool at {}", path.display());
+    // ydotool only works with the daemon running, but the socket location
+    // varies between versions, so a missing socket is a hint, not a failure.
+    let socket = std::env::var_os("YDOTOOL_SOCKET")
+        .map(PathBuf::from)
+        .into_iter()
+        .chain([
+            config::runtime_dir().join(".ydotool_socket"),
+            PathBuf::from("/tmp/.ydotool_socket"),
+        ])
+        .find(|p| p.exists());
+    Some(match socket {
+        Some(_) => Check::pass(label),
+        None => Check::warn(label, "ydotoold doesn't seem to be running; start it"),
+    })
+}
+
+/// The overlay is optional, so a missing binary only warns.
+#[cfg(feature = "osd")]
+fn check_osd(config: &Config) -> Option<Check> {
+    if !config.osd.autostart {
+        return None;
+    }
+    let beside_exe = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|dir| dir.join("whspr-osd")))
+        .filter(|p| p.exists());
+    Some(match beside_exe.or_else(|| find_in_path("whspr-osd")) {
+        Some(path) => Check::pass(format!("whspr-osd at {}", path.display())),
+        None => Check::warn(
+            "whspr-osd",
+            "not found next to whspr-rs or on PATH; the overlay won't show",
+        ),
+    })
+}
+
+#[cfg(not(feature = "osd"))]
+fn check_osd(_config: &Config) -> Option<Check> {
+    None
+}
+
+fn check_model(config: &Config) -> Option<Check> {
+    let model_path = config.resolved_model_path();
+    let label = format!("model {}", model_path.display());
+    Some(match validate::check_model(&model_path) {
+        Ok(()) => Check::pass(label),
+        Err(e) => Check::fail(
+            label,
+            format!("{e}; download one with `whspr-rs setup` or `whspr-rs model download`"),
+        ),
+    })
+}
+
+fn check_audio(config: &Config) -> Option<Check> {
+    let label = if config.audio.device.is_empty() {
+        "audio input (system default)".to_string()
+    } else {
+        format!("audio input \"{}\"", config.audio.device)
+    };
+    Some(match audio::find_input_device(&config.audio.device) {
+        Ok(_) => Check::pass(label),
+        Err(e) if config.audio.device.is_empty() => {
+            Check::fail(label, format!("{e}; check that a microphone is connected"))
+        }
+        Err(e) => Check::fail(
+            label,
+            format!("{e}; pick one from `whspr-rs devices` or clear audio.device"),
+        ),
+    })
+}
+
+/// Load the model with the configured GPU settings and transcribe the
+/// bundled start sound, so a broken model or GPU setup shows up here rather
+/// than on the first dictation. The clip is too short and quiet for the
+/// dictation gates, so it is padded with silence and fed straight to whisper.
+/// The clip has no speech, so whatever text comes back is shown, not checked.
+fn check_selftest(config: &Config) -> Option<Check> {
+    let model_path = config.resolved_model_path();
+    if !model_path.exists() {
+        // Already reported by check_model.
+        return None;
+    }
+    let mut audio = match file_audio::decode_bytes(
+        include_bytes!("../sounds/start.wav").to_vec(),
+        "bundled start sound",
+    ) {
+        Ok(audio) => audio,
+        Err(e) => return Some(Check::fail("self-test", e.to_string())),
+    };
+    audio.resize(
+        audio.len().max(file_audio::TARGET_SAMPLE_RATE as usize),
+        0.0,
+    );
+
+    let started = Instant::now();
+    let backend = match WhisperLocal::new(&config.whisper, &model_path) {
+        Ok(backend) => backend,
+        Err(e) => {
+            return Some(Check::fail(
+                "self-test: model load",
+                format!("{e}; try [whisper].use_gpu = false or a smaller model"),
+            ));
+        }
+    };
+    let loaded = started.elapsed();
+
+    let started = Instant::now();
+    let transcript = match backend.transcribe_ungated(&audio) {
+        Ok(transcript) => transcript,
+        Err(e) => {
+            return Some(Check::fail(
+                "self-test: transcription",
+                format!("{e}; try [whisper].use_gpu = false"),
+            ));
+        }
+    };
+    let text = transcribe::segments_to_text(&transcript.segments);
+    let heard = if text.is_empty() {
+        "no text".to_string()
+    } else {
+        format!("{:?}", text.trim())
+    };
+    Some(Check::pass(format!(
+        "self-test: model loaded in {:.1}s, transcribed in {:.1}s ({heard})",
+        loaded.as_secs_f64(),
+        started.elapsed().as_secs_f64()
+    )))
+}
+
+/// First executable named `bin` on PATH.
+fn find_in_path(bin: &str) -> Option<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(bin))
+        .find(|candidate| {
+            candidate
+                .metadata()
+                .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_in_path_finds_executables_only() {
+        assert!(find_in_path("sh").is_some());
+        assert!(find_in_path("definitely-not-a-real-binary").is_none());
+    }
+
+    #[test]
+    fn clipboard_mode_skips_uinput_check() {
+        let mut config = Config::default();
+        config.inject.mode = InjectMode::Clipboard;
+        assert!(check_uinput(&config).is_none());
+        config.inject.backend = InjectBackendKind::Ydotool;
+        assert!(check_clipboard(&config).is_none());
+        assert!(check_ydotool(&config).is_some());
+    }
+}
@@ -2,13 +2,44 @@ use std::io::Cursor;
 use std::sync::mpsc;
 use std::time::Duration;
 
-use rodio::{Decoder, OutputStreamBuilder, Sink};
+use rodio::stream::StreamError;
+use rodio::{Decoder, DeviceTrait, OutputStream, OutputStreamBuilder, Sink};
 
-use crate::error::{Result, WhsprError};
+use whspr_rs::config::{FeedbackConfig, SoundTheme};
+use whspr_rs::error::{Result, WhsprError};
 
-// Bundled sounds (embedded at compile time)
-const START_SOUND: &[u8] = include_bytes!("../sounds/start.wav");
-const STOP_SOUND: &[u8] = include_bytes!("../sounds/stop.wav");
+/// One bundled sound set, embedded at compile time.
+struct Sounds {
+    start: &'static [u8],
+    stop: &'static [u8],
+    complete: &'static [u8],
+    error: &'static [u8],
+    cancel: &'static [u8],
+}
+
+macro_rules! sounds {
+    ($dir:literal) => {
+        Sounds {
+            start: include_bytes!(concat!("../sounds/", $dir, "start.wav")),
+            stop: include_bytes!(concat!("../sounds/", $dir, "stop.wav")),
+            complete: include_bytes!(concat!("../sounds/", $dir, "complete.wav")),
+            error: include_bytes!(concat!("../sounds/", $dir, "error.wav")),
+            cancel: include_bytes!(concat!("../sounds/", $dir, "cancel.wav")),
+        }
+    };
+}
+
+const CHIME: Sounds = sounds!("");
+const BEEP: Sounds = sounds!("beep/");
+const CLICK: Sounds = sounds!("click/");
+
+fn theme_sounds(theme: SoundTheme) -> &'static Sounds {
+    match theme {
+        SoundTheme::Chime => &CHIME,
+        SoundTheme::Beep => &BEEP,
+        SoundTheme::Click => &CLICK,
+    }
+}
 
 enum SoundCommand {
     Play {
@@ -29,30 +60,33 @@ enum SoundCommand {
 /// `OutputStream` is torn down gracefully before the process exits.
 pub struct FeedbackPlayer {
     enabled: bool,
+    sounds: &'static Sounds,
     start_sound_path: Option<String>,
     stop_sound_path: Option<String>,
+    complete_sound_path: Option<String>,
+    error_sound_path: Option<String>,
+    cancel_sound_path: Option<String>,
     sender: Option<mpsc::Sender<SoundCommand>>,
     thread: Option<std::thread::JoinHandle<()>>,
 }
 
 impl FeedbackPlayer {
-    pub fn new(enabled: bool, start_sound: &str, stop_sound: &str) -> Self {
-        let start_sound_path = if start_sound.is_empty() {
-            None
-        } else {
-            Some(start_sound.to_string())
-        };
-        let stop_sound_path = if stop_sound.is_empty() {
-            None
-        } else {
-            Some(stop_sound.to_string())
-        };
+    pub fn new(config: &FeedbackConfig) -> Self {
+        let enabled = config.enabled;
+        let custom = |name: &str, path: &str| enabled.then(|| custom_sound(name, path)).flatten();
+        let start_sound_path = custom("start", &config.start_sound);
+        let stop_sound_path = custom("stop", &config.stop_sound);
+        let complete_sound_path = custom("complete", &config.complete_sound);
+        let error_sound_path = custom("error", &config.error_sound);
+        let cancel_sound_path = custom("cancel", &config.cancel_sound);
+        let volume = clamp_volume(config.volume);
+        let device = config.device.clone();
 
         let (sender, thread) = if enabled {
             let (sender, receiver) = mpsc::channel::<SoundCommand>();
             let thread = std::thread::spawn(move || {
                 // Lazily open the output stream so transient startup failures can recover.
-                let mut stream: Option<rodio::OutputStream> = None;
+                let mut stream: Option<OutputStream> = None;
 
                 while let Ok(cmd) = receiver.recv() {
                     match cmd {
@@ -62,7 +96,7 @@ impl FeedbackPlayer {
                             done,
                         } => {
                             if stream.is_none() {
-                                match OutputStreamBuilder::open_default_stream() {
+                                match open_output_stream(&device) {
                                     Ok(s) => stream = Some(s),
                                     Err(e) => {
                                         tracing::warn!(
@@ -80,6 +114,7 @@ impl FeedbackPlayer {
                                 stream.as_ref().expect("stream set"),
                                 custom_path.as_deref(),
                                 bundled,
+                                volume,
                             ) {
                                 tracing::warn!("failed to play feedback sound: {e}");
                             }
@@ -104,8 +139,12 @@ impl FeedbackPlayer {
 
         Self {
             enabled,
+            sounds: theme_sounds(config.theme),
             start_sound_path,
             stop_sound_path,
+            complete_sound_path,
+            error_sound_path,
+            cancel_sound_path,
             sender,
             thread,
         }
@@ -116,28 +155,7 @@ impl FeedbackPlayer {
     /// This ensures the sound completes before the mic goes live, preventing
     /// the start chime from leaking into the recording.
     pub fn play_start(&self) {
-        if !self.enabled {
-            return;
-        }
-        let sender = match self.sender.as_ref() {
-            Some(s) => s,
-            None => return,
-        };
-        let (tx, rx) = mpsc::sync_channel(1);
-        if sender
-            .send(SoundCommand::Play {
-                custom_path: self.start_sound_path.clone(),
-                bundled: START_SOUND,
-                done: Some(tx),
-            })
-            .is_err()
-        {
-            tracing::warn!("feedback thread unavailable, skipping start sound");
-            return;
-        }
-        if rx.recv_timeout(Duration::from_secs(2)).is_err() {
-            tracing::warn!("timed out waiting for start sound playback");
-        }
+        self.play_blocking(self.start_sound_path.clone(), self.sounds.start, "start");
     }
 
     /// Blocks until the stop sound has finished playing.
@@ -145,6 +163,31 @@ impl FeedbackPlayer {
     /// This prevents the process from exiting (and tearing down the audio
     /// stream) while the sound is still in-flight.
     pub fn play_stop(&self) {
+        self.play_blocking(self.stop_sound_path.clone(), self.sounds.stop, "stop");
+    }
+
+    /// Blocks until the transcription-complete sound has finished playing,
+    /// so it is over by the time the text lands.
+    pub fn play_complete(&self) {
+        self.play_blocking(
+            self.complete_sound_path.clone(),
+            self.sounds.complete,
+            "complete",
+        );
+    }
+
+    /// Blocks until the error sound has finished playing, so it isn't cut
+    /// off when the process exits with the error.
+    pub fn play_error(&self) {
+        self.play_blocking(self.error_sound_path.clone(), self.sounds.error, "error");
+    }
+
+    /// Blocks until the cancel sound has finished playing.
+    pub fn play_cancel(&self) {
+        self.play_blocking(self.cancel_sound_path.clone(), self.sounds.cancel, "cancel");
+    }
+
+    fn play_blocking(&self, custom_path: Option<String>, bundled: &'static [u8], name: &str) {
         if !self.enabled {
             return;
         }
@@ -155,17 +198,17 @@ impl FeedbackPlayer {
         let (tx, rx) = mpsc::sync_channel(1);
         if sender
             .send(SoundCommand::Play {
-                custom_path: self.stop_sound_path.clone(),
-                bundled: STOP_SOUND,
+                custom_path,
+                bundled,
                 done: Some(tx),
             })
             .is_err()
         {
-            tracing::warn!("feedback thread unavailable, skipping stop sound");
+            tracing::warn!("feedback thread unavailable, skipping {name} sound");
             return;
         }
         if rx.recv_timeout(Duration::from_secs(2)).is_err() {
-            tracing::warn!("timed out waiting for stop sound playback");
+            tracing::warn!("timed out waiting for {name} sound playback");
         }
     }
 }
@@ -181,20 +224,84 @@ impl Drop for FeedbackPlayer {
     }
 }
 
+/// Checks a configured sound up front so a bad path is reported once at
+/// startup rather than silently skipped at playback. Falls back to the
+/// bundled sound when the file can't be used.
+fn custom_sound(name: &str, path: &str) -> Option<String> {
+    if path.is_empty() {
+        return None;
+    }
+    match open_sound_file(path) {
+        Ok(_) => Some(path.to_string()),
+        Err(e) => {
+            tracing::warn!("feedback.{name}_sound: {e}; using the bundled sound instead");
+            None
+        }
+    }
+}
+
+fn open_sound_file(path: &str) -> Result<Decoder<std::io::BufReader<std::fs::File>>> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| WhsprError::Feedback(format!("failed to open sound file {path}: {e}")))?;
+    Decoder::new(std::io::BufReader::new(file))
+        .map_err(|e| WhsprError::Feedback(format!("failed to decode sound file {path}: {e}")))
+}
+
+/// Open the named output device, falling back to the default one.
+fn open_output_stream(device: &str) -> std::result::Result<OutputStream, StreamError> {
+    if !device.is_empty() {
+        match find_output_device(device) {
+            Some(found) => {
+                match OutputStreamBuilder::from_device(found).and_then(|b| b.open_stream()) {
+                    Ok(stream) => return Ok(stream),
+                    Err(e) => tracing::warn!(
+                        "failed to open feedback.device '{device}', using the default output: {e}"
+                    ),
+                }
+            }
+            None => {
+                tracing::warn!("feedback.device '{device}' not found, using the default output")
+            }
+        }
+    }
+    OutputStreamBuilder::open_default_stream()
+}
+
+/// First output device whose name contains `spec`. Uses rodio's cpal, which
+/// can differ from the one used for input.
+fn find_output_device(spec: &str) -> Option<rodio::cpal::Device> {
+    use rodio::cpal::traits::HostTrait;
+
+    rodio::cpal::default_host()
+        .output_devices()
+        .ok()?
+        .find(|d| d.name().is_ok_and(|name| name.contains(spec)))
+}
+
+fn clamp_volume(volume: f32) -> f32 {
+    if (0.0..=1.0).contains(&volume) {
+        return volume;
+    }
+    let clamped = if volume.is_nan() {
+        1.0
+    } else {
+        volume.clamp(0.0, 1.0)
+    };
+    tracing::warn!("feedback.volume {volume} is outside 0.0-1.0, using {clamped}");
+    clamped
+}
+
 fn play_on_stream(
-    stream: &rodio::OutputStream,
+    stream: &OutputStream,
     custom_path: Option<&str>,
     bundled: &'static [u8],
+    volume: f32,
 ) -> Result<()> {
     let sink = Sink::connect_new(stream.mixer());
+    sink.set_volume(volume);
 
     if let Some(path) = custom_path {
-        let file = std::fs::File::open(path)
-            .map_err(|e| WhsprError::Feedback(format!("failed to open sound file: {e}")))?;
-        let reader = std::io::BufReader::new(file);
-        let source = Decoder::new(reader)
-            .map_err(|e| WhsprError::Feedback(format!("failed to decode sound file: {e}")))?;
-        sink.append(source);
+        sink.append(open_sound_file(path)?);
     } else {
         let cursor = Cursor::new(bundled);
         let source = Decoder::new(cursor)
@@ -212,14 +319,61 @@ mod tests {
 
     #[test]
     fn disabled_feedback_is_noop() {
-        let player = FeedbackPlayer::new(false, "", "");
+        let player = FeedbackPlayer::new(&FeedbackConfig {
+            enabled: false,
+            ..FeedbackConfig::default()
+        });
         player.play_start();
         player.play_stop();
+        player.play_complete();
+        player.play_error();
+        player.play_cancel();
+    }
+
+    #[test]
+    fn bundled_themes_decode() {
+        for theme in [SoundTheme::Chime, SoundTheme::Beep, SoundTheme::Click] {
+            let sounds = theme_sounds(theme);
+            for bytes in [
+                sounds.start,
+                sounds.stop,
+                sounds.complete,
+                sounds.error,
+                sounds.cancel,
+            ] {
+                assert!(Decoder::new(Cursor::new(bytes)).is_ok(), "{theme:?}");
+            }
+        }
     }
 
     #[test]
     fn dropping_feedback_player_does_not_panic() {
-        let player = FeedbackPlayer::new(true, "", "");
+        let player = FeedbackPlayer::new(&FeedbackConfig::default());
         drop(player);
     }
+
+    #[test]
+    fn custom_sound_falls_back_when_unusable() {
+        assert_eq!(custom_sound("start", ""), None);
+        assert_eq!(custom_sound("start", "/nonexistent/start.wav"), None);
+
+        let garbage = crate::test_support::unique_temp_path("feedback-garbage", "wav");
+        std::fs::write(&garbage, b"not a sound").unwrap();
+        assert_eq!(custom_sound("start", garbage.to_str().unwrap()), None);
+        let _ = std::fs::remove_file(&garbage);
+
+        let valid = crate::test_support::unique_temp_path("feedback-valid", "wav");
+        std::fs::write(&valid, CHIME.start).unwrap();
+        let valid = valid.to_str().unwrap().to_string();
+        assert_eq!(custom_sound("start", &valid), Some(valid.clone()));
+        let _ = std::fs::remove_file(&valid);
+    }
+
+    #[test]
+    fn clamp_volume_keeps_range_and_rejects_nan() {
+        assert_eq!(clamp_volume(0.3), 0.3);
+        assert_eq!(clamp_volume(-1.0), 0.0);
+        assert_eq!(clamp_volume(2.5), 1.0);
+        assert_eq!(clamp_volume(f32::NAN), 1.0);
+    }
 }
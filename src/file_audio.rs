@@ -1,5 +1,6 @@
-use std::io::BufReader;
+use std::io::{BufReader, Cursor, Read, Seek, Write};
 use std::path::Path;
+use std::sync::Arc;
 
 use rodio::Decoder;
 use rodio::source::UniformSourceIterator;
@@ -8,37 +9,310 @@ use crate::error::{Result, WhsprError};
 
 pub const TARGET_SAMPLE_RATE: u32 = 16000;
 
+/// An audio format recognised by extension or magic bytes. `hint` and `mime`
+/// steer rodio's probe; unsupported formats get a clear error instead of the
+/// decoder's generic one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Format {
+    name: &'static str,
+    hint: &'static str,
+    mime: &'static str,
+    supported: bool,
+}
+
+const fn format(name: &'static str, hint: &'static str, mime: &'static str) -> Format {
+    Format {
+        name,
+        hint,
+        mime,
+        supported: true,
+    }
+}
+
+const WAV: Format = format("WAV", "wav", "audio/vnd.wav");
+const FLAC: Format = format("FLAC", "flac", "audio/flac");
+const MP3: Format = format("MP3", "mp3", "audio/mpeg");
+const OGG_VORBIS: Format = format("Ogg Vorbis", "ogg", "audio/ogg");
+const MP4: Format = format("MP4/M4A", "m4a", "audio/mp4");
+const AAC: Format = format("AAC", "aac", "audio/aac");
+const WEBM: Format = format("WebM/Matroska", "webm", "audio/webm");
+// No Opus codec is built in, whether in Ogg or in WebM/Matroska.
+const OPUS: Format = Format {
+    supported: false,
+    ..format("Opus", "opus", "audio/opus")
+};
+const WEBM_OPUS: Format = Format {
+    supported: false,
+    ..format("Opus", "webm", "audio/webm")
+};
+
+fn format_from_extension(path: &Path) -> Option<Format> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "wav" | "wave" => WAV,
+        "flac" => FLAC,
+        "mp3" => MP3,
+        "ogg" | "oga" => OGG_VORBIS,
+        "m4a" | "m4b" | "mp4" => MP4,
+        "aac" => AAC,
+        "opus" => OPUS,
+        "webm" | "mkv" | "mka" => WEBM,
+        _ => return None,
+    })
+}
+
+/// The format `header`, the first bytes of a file, starts like.
+fn sniff_format(header: &[u8]) -> Option<Format> {
+    match header {
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'A',
+            b'V',
+            b'E',
+            ..,
+        ] => Some(WAV),
+        [b'f', b'L', b'a', b'C', ..] => Some(FLAC),
+        [b'I', b'D', b'3', ..] => Some(MP3),
+        [b'O', b'g', b'g', b'S', ..] => {
+            let opus = header.windows(8).any(|w| w == b"OpusHead");
+            Some(if opus { OPUS } else { OGG_VORBIS })
+        }
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some(MP4),
+        [0x1a, 0x45, 0xdf, 0xa3, ..] => {
+            let opus = header.windows(6).any(|w| w == b"A_OPUS");
+            Some(if opus { WEBM_OPUS } else { WEBM })
+        }
+        // Frame syncs: ADTS AAC has layer bits 00, MPEG audio doesn't.
+        [0xff, b, ..] if b & 0xf6 == 0xf0 => Some(AAC),
+        [0xff, b, ..] if b & 0xe0 == 0xe0 && b & 0x06 != 0 => Some(MP3),
+        _ => None,
+    }
+}
+
+/// Enough to find every signature `sniff_format` looks for, including the
+/// codec ID in a Matroska track list, which follows the segment info.
+const SNIFF_LEN: usize = 4096;
+
 /// Decode an audio file to mono 16 kHz f32 samples suitable for Whisper.
 pub fn decode_audio_file(path: &Path) -> Result<Vec<f32>> {
-    let file = std::fs::File::open(path)
-        .map_err(|e| WhsprError::Audio(format!("failed to open {}: {e}", path.display())))?;
+    let open = || {
+        std::fs::File::open(path)
+            .map(BufReader::new)
+            .map_err(|e| WhsprError::Audio(format!("failed to open {}: {e}", path.display())))
+    };
+    let mut header = Vec::with_capacity(SNIFF_LEN);
+    open()?
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut header)
+        .map_err(|e| WhsprError::Audio(format!("failed to read {}: {e}", path.display())))?;
 
-    let reader = BufReader::new(file);
+    let detected = sniff_format(&header);
+    let hint = format_from_extension(path).or(detected);
+    decode(open, hint, detected, &path.display().to_string())
+}
 
-    let decoder = Decoder::new(reader)
-        .map_err(|e| WhsprError::Audio(format!("failed to decode {}: {e}", path.display())))?;
+/// Decode audio piped to stdin. Decoders need to seek, so the whole stream
+/// is buffered first; the format is detected from the content.
+pub fn decode_stdin() -> Result<Vec<f32>> {
+    let mut bytes = Vec::new();
+    std::io::stdin()
+        .read_to_end(&mut bytes)
+        .map_err(|e| WhsprError::Audio(format!("failed to read stdin: {e}")))?;
+    decode_bytes(bytes, "stdin")
+}
 
-    let resampled = UniformSourceIterator::<Decoder<BufReader<std::fs::File>>>::new(
-        decoder,
-        1,
-        TARGET_SAMPLE_RATE,
-    );
+/// Decode in-memory audio; `label` names it in errors and logs.
+pub fn decode_bytes(bytes: Vec<u8>, label: &str) -> Result<Vec<f32>> {
+    if bytes.is_empty() {
+        return Err(WhsprError::Audio(format!("no audio data on {label}")));
+    }
+    let detected = sniff_format(&bytes[..bytes.len().min(SNIFF_LEN)]);
+    let bytes: Arc<[u8]> = bytes.into();
+    decode(
+        || Ok(Cursor::new(Arc::clone(&bytes))),
+        detected,
+        detected,
+        label,
+    )
+}
+
+/// Decode what `open` reads, trying the `hint` format first and then plain
+/// probing. `detected` is what the content looked like, for the error.
+fn decode<R: Read + Seek + Send + Sync + 'static>(
+    open: impl Fn() -> Result<R>,
+    hint: Option<Format>,
+    detected: Option<Format>,
+    label: &str,
+) -> Result<Vec<f32>> {
+    let hinted = hint.filter(|format| format.supported).and_then(|format| {
+        let reader = open().ok()?;
+        Decoder::builder()
+            .with_data(reader)
+            .with_hint(format.hint)
+            .with_mime_type(format.mime)
+            .build()
+            .inspect_err(|e| {
+                tracing::debug!(
+                    "decoding {label} as {} failed, probing it: {e}",
+                    format.name
+                )
+            })
+            .ok()
+    });
+    let decoder = match hinted {
+        Some(decoder) => decoder,
+        None => Decoder::new(open()?).map_err(|e| decode_error(label, detected.or(hint), e))?,
+    };
+
+    let resampled = UniformSourceIterator::<Decoder<R>>::new(decoder, 1, TARGET_SAMPLE_RATE);
 
     let samples: Vec<f32> = resampled.collect();
 
     if samples.is_empty() {
         return Err(WhsprError::Audio(format!(
-            "no audio samples decoded from {}",
-            path.display()
+            "no audio samples decoded from {label}"
         )));
     }
 
     tracing::info!(
-        "decoded {}: {:.1}s, {} samples",
-        path.display(),
+        "decoded {label}: {:.1}s, {} samples",
         samples.len() as f64 / TARGET_SAMPLE_RATE as f64,
         samples.len()
     );
 
     Ok(samples)
 }
+
+fn decode_error(
+    label: &str,
+    format: Option<Format>,
+    e: rodio::decoder::DecoderError,
+) -> WhsprError {
+    WhsprError::Audio(match format {
+        Some(format) if !format.supported => format!(
+            "failed to decode {label}: {} audio is not supported, convert it to WAV or FLAC first (e.g. with ffmpeg)",
+            format.name
+        ),
+        Some(format) => format!("failed to decode {label} as {}: {e}", format.name),
+        None => format!("failed to decode {label}, format not recognized: {e}"),
+    })
+}
+
+/// Write mono f32 samples as a 16-bit PCM WAV file.
+pub fn write_wav(path: &Path, samples: &[f32], sample_rate: u32) -> Result<()> {
+    let data_len = u32::try_from(samples.len() * 2)
+        .map_err(|_| WhsprError::Audio("recording too long for a WAV file".into()))?;
+    let mut out = Vec::with_capacity(44 + data_len as usize);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    out.extend_from_slice(&1u16.to_le_bytes()); // mono
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&(sample_rate * 2).to_le_bytes()); // byte rate
+    out.extend_from_slice(&2u16.to_le_bytes()); // block align
+    out.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for &sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+
+    std::fs::File::create(path)
+        .and_then(|mut file| file.write_all(&out))
+        .map_err(|e| WhsprError::Audio(format!("failed to write {}: {e}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_bytes_detects_format_from_content() {
+        let wav = include_bytes!("../sounds/start.wav").to_vec();
+        let samples = decode_bytes(wav, "stdin").expect("bundled wav decodes");
+        assert!(!samples.is_empty());
+    }
+
+    #[test]
+    fn decode_bytes_rejects_empty_and_garbage_input() {
+        assert!(decode_bytes(Vec::new(), "stdin").is_err());
+        assert!(decode_bytes(b"not audio at all".to_vec(), "stdin").is_err());
+    }
+
+    #[test]
+    fn sniff_format_recognizes_common_containers() {
+        let wav = include_bytes!("../sounds/start.wav");
+        assert_eq!(sniff_format(wav), Some(WAV));
+        assert_eq!(sniff_format(b"fLaC\0\0\0\x22"), Some(FLAC));
+        assert_eq!(sniff_format(b"ID3\x04\0"), Some(MP3));
+        assert_eq!(sniff_format(&[0xff, 0xfb, 0x90, 0x64]), Some(MP3));
+        assert_eq!(sniff_format(&[0xff, 0xf1, 0x50, 0x80]), Some(AAC));
+        assert_eq!(sniff_format(b"\0\0\0\x20ftypM4A "), Some(MP4));
+        assert_eq!(
+            sniff_format(b"OggS\0\x02 padding \x01\x1evorbis"),
+            Some(OGG_VORBIS)
+        );
+        assert_eq!(sniff_format(b"OggS\0\x02 padding OpusHead"), Some(OPUS));
+        assert_eq!(sniff_format(&[0x1a, 0x45, 0xdf, 0xa3, 0x9f]), Some(WEBM));
+        assert_eq!(
+            sniff_format(b"\x1a\x45\xdf\xa3 webm tracks \x86\x86A_OPUS"),
+            Some(WEBM_OPUS)
+        );
+        assert_eq!(sniff_format(b"not audio at all"), None);
+    }
+
+    #[test]
+    fn format_from_extension_ignores_case() {
+        assert_eq!(format_from_extension(Path::new("a/Note.M4A")), Some(MP4));
+        assert_eq!(format_from_extension(Path::new("call.opus")), Some(OPUS));
+        assert_eq!(format_from_extension(Path::new("clip.webm")), Some(WEBM));
+        assert_eq!(format_from_extension(Path::new("README")), None);
+    }
+
+    #[test]
+    fn decode_audio_file_probes_past_a_wrong_extension() {
+        let path = crate::test_support::unique_temp_path("misnamed", "m4a");
+        std::fs::write(&path, include_bytes!("../sounds/start.wav")).unwrap();
+        let result = decode_audio_file(&path);
+        let _ = std::fs::remove_file(&path);
+        assert!(!result.expect("wav named .m4a decodes").is_empty());
+    }
+
+    #[test]
+    fn decode_bytes_names_unsupported_formats() {
+        let opus = b"OggS\0\x02\0\0\0\0\0\0\0\0 OpusHead\x01\x01".to_vec();
+        let err = decode_bytes(opus, "stdin").unwrap_err().to_string();
+        assert!(err.contains("Opus audio is not supported"), "{err}");
+
+        let webm_opus = b"\x1a\x45\xdf\xa3\x9f webm tracks \x86\x86A_OPUS".to_vec();
+        let err = decode_bytes(webm_opus, "stdin").unwrap_err().to_string();
+        assert!(err.contains("Opus audio is not supported"), "{err}");
+    }
+
+    #[test]
+    fn write_wav_round_trips_through_decoder() {
+        let path = crate::test_support::unique_temp_path("debug-audio", "wav");
+        let samples: Vec<f32> = (0..1600)
+            .map(|i| ((i % 100) as f32 / 100.0) - 0.5)
+            .collect();
+        write_wav(&path, &samples, TARGET_SAMPLE_RATE).unwrap();
+
+        let decoded = decode_audio_file(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(decoded.len(), samples.len());
+        for (a, b) in decoded.iter().zip(&samples) {
+            assert!((a - b).abs() < 1e-3, "{a} vs {b}");
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::process::Command;
+
+use serde_json::Value;
+
+use whspr_rs::config::{InjectConfig, WindowQueryKind};
+use whspr_rs::error::{Result, WhsprError};
+
+/// Looks up the app_id (or X11 class) of the focused window. One per
+/// compositor, since Wayland has no common way to ask.
+trait WindowQuery: Send + Sync {
+    fn focused_app(&self) -> Result<Option<String>>;
+
+    fn name(&self) -> &'static str;
+}
+
+struct Hyprland;
+
+impl WindowQuery for Hyprland {
+    fn focused_app(&self) -> Result<Option<String>> {
+        let json = run_json("hyprctl", &["activewindow", "-j"])?;
+        Ok(hyprland_app(&json))
+    }
+
+    fn name(&self) -> &'static str {
+        "hyprctl"
+    }
+}
+
+struct Sway;
+
+impl WindowQuery for Sway {
+    fn focused_app(&self) -> Result<Option<String>> {
+        let json = run_json("swaymsg", &["-t", "get_tree"])?;
+        Ok(sway_app(&json))
+    }
+
+    fn name(&self) -> &'static str {
+        "swaymsg"
+    }
+}
+
+/// Refuses injection into windows listed in `[inject].deny_apps`.
+pub struct FocusGuard {
+    deny_apps: Vec<String>,
+    query: Option<Box<dyn WindowQuery>>,
+}
+
+impl FocusGuard {
+    pub fn new(config: &InjectConfig) -> Self {
+        let query: Option<Box<dyn WindowQuery>> = if config.deny_apps.is_empty() {
+            None
+        } else {
+            match config.window_query {
+                WindowQueryKind::Hyprland => Some(Box::new(Hyprland)),
+                WindowQueryKind::Sway => Some(Box::new(Sway)),
+                WindowQueryKind::Auto => detect_compositor(),
+            }
+        };
+        if !config.deny_apps.is_empty() && query.is_none() {
+            tracing::warn!(
+                "[inject].deny_apps is set but the compositor isn't Hyprland or Sway; \
+                 set [inject].window_query to enable it"
+            );
+        }
+        Self {
+            deny_apps: config.deny_apps.clone(),
+            query,
+        }
+    }
+
+    /// The focused app if it is denied. A failed lookup is logged and lets
+    /// the text through, so a broken helper doesn't stop all dictation.
+    pub fn blocked_app(&self) -> Option<String> {
+        let query = self.query.as_ref()?;
+        let app = match query.focused_app() {
+            Ok(app) => app?,
+            Err(e) => {
+                tracing::warn!(
+                    "failed to query the focused window via {}: {e}",
+                    query.name()
+                );
+                return None;
+            }
+        };
+        tracing::debug!("focused window: {app}");
+        is_denied(&app, &self.deny_apps).then_some(app)
+    }
+}
+
+fn detect_compositor() -> Option<Box<dyn WindowQuery>> {
+    if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        Some(Box::new(Hyprland))
+    } else if std::env::var_os("SWAYSOCK").is_some() {
+        Some(Box::new(Sway))
+    } else {
+        None
+    }
+}
+
+fn is_denied(app: &str, deny_apps: &[String]) -> bool {
+    deny_apps
+        .iter()
+        .any(|denied| denied.eq_ignore_ascii_case(app))
+}
+
+fn run_json(bin: &str, args: &[&str]) -> Result<Value> {
+    let output = Command::new(bin)
+        .args(args)
+        .output()
+        .map_err(|e| WhsprError::Injection(format!("failed to run {bin}: {e}")))?;
+    if !output.status.success() {
+        return Err(WhsprError::Injection(format!(
+            "{bin} exited with {}",
+            output.status
+        )));
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| WhsprError::Injection(format!("unexpected {bin} output: {e}")))
+}
+
+/// `hyprctl activewindow -j` prints `{}` when nothing is focused.
+fn hyprland_app(json: &Value) -> Option<String> {
+    json.get("class")
+        .and_then(Value::as_str)
+        .filter(|class| !class.is_empty())
+        .map(str::to_string)
+}
+
+/// Walk the sway tree to the focused window: `app_id` for Wayland clients,
+/// `window_properties.class` for XWayland ones.
+fn sway_app(node: &Value) -> Option<String> {
+    if node.get("focused").and_then(Value::as_bool) == Some(true) {
+        return node
+            .get("app_id")
+            .and_then(Value::as_str)
+            .or_else(|| node.pointer("/window_properties/class")?.as_str())
+            .map(str::to_string);
+    }
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node.get(key)?.as_array())
+        .flatten()
+        .find_map(sway_app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hyprland_app_reads_class() {
+        let json: Value = serde_json::from_str(r#"{"class":"pinentry-qt","title":"x"}"#).unwrap();
+        assert_eq!(hyprland_app(&json).as_deref(), Some("pinentry-qt"));
+        assert_eq!(hyprland_app(&serde_json::json!({})), None);
+    }
+
+    #[test]
+    fn sway_app_finds_focused_node() {
+        let tree = serde_json::json!({
+            "focused": false,
+            "nodes": [{
+                "focused": false,
+                "nodes": [{"focused": false, "app_id": "kitty"}],
+                "floating_nodes": [{
+                    "focused": true,
+                    "app_id": null,
+                    "window_properties": {"class": "KeePassXC"}
+                }]
+            }]
+        });
+        assert_eq!(sway_app(&tree).as_deref(), Some("KeePassXC"));
+    }
+
+    #[test]
+    fn is_denied_ignores_case() {
+        let deny = vec!["org.keepassxc.KeePassXC".to_string()];
+        assert!(is_denied("org.keepassxc.keepassxc", &deny));
+        assert!(!is_denied("firefox", &deny));
+    }
+}
@@ -1,29 +1,139 @@
 use std::process::{Command, Stdio};
+use std::sync::Arc;
 use std::time::Duration;
 
 use evdev::uinput::VirtualDevice;
 use evdev::{AttributeSet, EventType, InputEvent, KeyCode};
 
+use crate::config::{InjectBackendKind, InjectConfig, InjectMode, Selection};
 use crate::error::{Result, WhsprError};
+use crate::keys;
 
-pub struct TextInjector {
+/// A way of delivering text to the focused window. `inject` runs on a
+/// blocking thread, so implementations may sleep and spawn processes.
+trait InjectBackend: Send + Sync {
+    fn inject(&self, text: &str) -> Result<()>;
+
+    /// Short description for logs, e.g. "wl-copy + LEFTCTRL+LEFTSHIFT+V".
+    fn describe(&self) -> String;
+}
+
+/// Clipboard via wl-copy plus keystrokes from a uinput virtual keyboard.
+struct UinputBackend {
+    mode: InjectMode,
+    paste_keys: Vec<KeyCode>,
+    clipboard_delay: Duration,
+    selection: Selection,
     wl_copy_bin: String,
     wl_copy_args: Vec<String>,
 }
 
-impl TextInjector {
-    pub fn new() -> Self {
-        Self {
-            wl_copy_bin: "wl-copy".to_string(),
-            wl_copy_args: Vec::new(),
+impl UinputBackend {
+    /// Put `text` on the configured selection(s).
+    fn copy(&self, text: &str) -> Result<()> {
+        if self.selection != Selection::Primary {
+            run_wl_copy(&self.wl_copy_bin, &self.wl_copy_args, text)?;
+        }
+        if self.selection != Selection::Clipboard {
+            let mut args = self.wl_copy_args.clone();
+            args.push("--primary".into());
+            run_wl_copy(&self.wl_copy_bin, &args, text)?;
+        }
+        Ok(())
+    }
+}
+
+impl InjectBackend for UinputBackend {
+    fn inject(&self, text: &str) -> Result<()> {
+        match self.mode {
+            // Paste keys read the clipboard, so primary-only means copy only.
+            InjectMode::Paste if self.selection == Selection::Primary => self.copy(text),
+            InjectMode::Paste => {
+                inject_sync(&self.paste_keys, self.clipboard_delay, || self.copy(text))
+            }
+            InjectMode::Clipboard => self.copy(text),
+            InjectMode::Type => type_sync(text),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self.mode {
+            InjectMode::Paste if self.selection == Selection::Primary => {
+                "wl-copy (primary selection only)".into()
+            }
+            InjectMode::Paste => {
+                let combo: Vec<String> = self
+                    .paste_keys
+                    .iter()
+                    .map(|key| format!("{key:?}").trim_start_matches("KEY_").to_string())
+                    .collect();
+                format!("wl-copy + {}", combo.join("+"))
+            }
+            InjectMode::Clipboard => "wl-copy (clipboard only)".into(),
+            InjectMode::Type => "uinput typing".into(),
         }
     }
+}
+
+/// Types text through a running ydotoold, for setups where uinput needs root.
+struct YdotoolBackend {
+    bin: String,
+}
+
+impl InjectBackend for YdotoolBackend {
+    fn inject(&self, text: &str) -> Result<()> {
+        // Typing speed is bounded by ydotool's per-key delay, so scale the
+        // timeout with the text length.
+        let timeout = Duration::from_secs(2) + Duration::from_millis(20) * text.len() as u32;
+        let args = ["type".to_string(), "--file".to_string(), "-".to_string()];
+        run_with_stdin("ydotool", &self.bin, &args, text, timeout)
+    }
+
+    fn describe(&self) -> String {
+        "ydotool type".into()
+    }
+}
+
+pub struct TextInjector {
+    backend: Arc<dyn InjectBackend>,
+}
+
+impl TextInjector {
+    /// Build the configured backend. Fails if `paste_keys` contains an unknown
+    /// key name so a typo surfaces at startup rather than at paste time.
+    pub fn new(config: &InjectConfig) -> Result<Self> {
+        let backend: Arc<dyn InjectBackend> = match config.backend {
+            InjectBackendKind::Uinput => Arc::new(UinputBackend {
+                mode: config.mode,
+                paste_keys: keys::parse_key_combo(&config.paste_keys)?,
+                clipboard_delay: Duration::from_millis(config.clipboard_delay_ms),
+                selection: config.selection,
+                wl_copy_bin: "wl-copy".to_string(),
+                wl_copy_args: Vec::new(),
+            }),
+            // Config validation only allows ydotool with mode = "type".
+            InjectBackendKind::Ydotool => Arc::new(YdotoolBackend {
+                bin: "ydotool".to_string(),
+            }),
+        };
+        Ok(Self { backend })
+    }
 
     #[cfg(test)]
-    fn with_wl_copy_command(bin: &str, args: &[&str]) -> Self {
+    fn with_wl_copy_command(mode: InjectMode, bin: &str, args: &[&str]) -> Self {
         Self {
-            wl_copy_bin: bin.to_string(),
-            wl_copy_args: args.iter().map(|arg| (*arg).to_string()).collect(),
+            backend: Arc::new(UinputBackend {
+                mode,
+                paste_keys: vec![
+                    KeyCode::KEY_LEFTCTRL,
+                    KeyCode::KEY_LEFTSHIFT,
+                    KeyCode::KEY_V,
+                ],
+                clipboard_delay: Duration::from_millis(180),
+                selection: Selection::Clipboard,
+                wl_copy_bin: bin.to_string(),
+                wl_copy_args: args.iter().map(|arg| (*arg).to_string()).collect(),
+            }),
         }
     }
 
@@ -35,24 +145,31 @@ impl TextInjector {
 
         let text = text.to_string();
         let text_len = text.len();
-        let wl_copy_bin = self.wl_copy_bin.clone();
-        let wl_copy_args = self.wl_copy_args.clone();
-        tokio::task::spawn_blocking(move || inject_sync(&wl_copy_bin, &wl_copy_args, &text))
+        let backend = Arc::clone(&self.backend);
+        tokio::task::spawn_blocking(move || backend.inject(&text))
             .await
             .map_err(|e| WhsprError::Injection(format!("injection task panicked: {e}")))??;
 
-        tracing::info!("injected {} chars via wl-copy + Ctrl+Shift+V", text_len);
+        tracing::info!(
+            "injected {} chars via {}",
+            text_len,
+            self.backend.describe()
+        );
         Ok(())
     }
 }
 
-fn inject_sync(wl_copy_bin: &str, wl_copy_args: &[String], text: &str) -> Result<()> {
+fn inject_sync(
+    paste_keys: &[KeyCode],
+    clipboard_delay: Duration,
+    copy: impl FnOnce() -> Result<()>,
+) -> Result<()> {
     // Create uinput device early so it registers with the compositor
     // while wl-copy + clipboard delay run in parallel.
     let mut keys = AttributeSet::<KeyCode>::new();
-    keys.insert(KeyCode::KEY_LEFTCTRL);
-    keys.insert(KeyCode::KEY_LEFTSHIFT);
-    keys.insert(KeyCode::KEY_V);
+    for key in paste_keys {
+        keys.insert(*key);
+    }
 
     let mut device = VirtualDevice::builder()
         .map_err(|e| WhsprError::Injection(format!("uinput: {e}")))?
@@ -62,17 +179,166 @@ fn inject_sync(wl_copy_bin: &str, wl_copy_args: &[String], text: &str) -> Result
         .build()
         .map_err(|e| WhsprError::Injection(format!("uinput build: {e}")))?;
 
-    run_wl_copy(wl_copy_bin, wl_copy_args, text)?;
+    copy()?;
 
     // Wait for compositor to process the clipboard offer.
     // The uinput device was created above, so it has already been
     // registering during the wl-copy write.
+    std::thread::sleep(clipboard_delay);
+    emit_paste_combo(&mut device, paste_keys)?;
+
+    Ok(())
+}
+
+fn type_sync(text: &str) -> Result<()> {
+    let mut keys = AttributeSet::<KeyCode>::new();
+    keys.insert(KeyCode::KEY_LEFTSHIFT);
+    for (key, _) in (' '..='~').chain(['\n', '\t']).filter_map(char_to_key) {
+        keys.insert(key);
+    }
+
+    let mut device = VirtualDevice::builder()
+        .map_err(|e| WhsprError::Injection(format!("uinput: {e}")))?
+        .name("whspr-rs-keyboard")
+        .with_keys(&keys)
+        .map_err(|e| WhsprError::Injection(format!("uinput keys: {e}")))?
+        .build()
+        .map_err(|e| WhsprError::Injection(format!("uinput build: {e}")))?;
+
+    // Give the compositor time to register the new device before typing.
     std::thread::sleep(Duration::from_millis(180));
-    emit_paste_combo(&mut device)?;
+
+    let mut skipped = 0;
+    for c in text.chars() {
+        let Some((key, shift)) = char_to_key(c) else {
+            skipped += 1;
+            continue;
+        };
+        emit_key(&mut device, key, shift)?;
+    }
+    if skipped > 0 {
+        tracing::warn!("skipped {skipped} characters with no key mapping while typing");
+    }
 
     Ok(())
 }
 
+fn emit_key(device: &mut VirtualDevice, key: KeyCode, shift: bool) -> Result<()> {
+    if shift {
+        device
+            .emit(&[InputEvent::new(
+                EventType::KEY.0,
+                KeyCode::KEY_LEFTSHIFT.0,
+                1,
+            )])
+            .map_err(|e| WhsprError::Injection(format!("shift press: {e}")))?;
+    }
+    device
+        .emit(&[
+            InputEvent::new(EventType::KEY.0, key.0, 1),
+            InputEvent::new(EventType::KEY.0, key.0, 0),
+        ])
+        .map_err(|e| WhsprError::Injection(format!("key press: {e}")))?;
+    if shift {
+        device
+            .emit(&[InputEvent::new(
+                EventType::KEY.0,
+                KeyCode::KEY_LEFTSHIFT.0,
+                0,
+            )])
+            .map_err(|e| WhsprError::Injection(format!("shift release: {e}")))?;
+    }
+    std::thread::sleep(Duration::from_millis(8));
+    Ok(())
+}
+
+/// Map a character to its key on a US layout, plus whether Shift is needed.
+fn char_to_key(c: char) -> Option<(KeyCode, bool)> {
+    let shift = c.is_ascii_uppercase();
+    let key = match c.to_ascii_lowercase() {
+        'a' => KeyCode::KEY_A,
+        'b' => KeyCode::KEY_B,
+        'c' => KeyCode::KEY_C,
+        'd' => KeyCode::KEY_D,
+        'e' => KeyCode::KEY_E,
+        'f' => KeyCode::KEY_F,
+        'g' => KeyCode::KEY_G,
+        'h' => KeyCode::KEY_H,
+        'i' => KeyCode::KEY_I,
+        'j' => KeyCode::KEY_J,
+        'k' => KeyCode::KEY_K,
+        'l' => KeyCode::KEY_L,
+        'm' => KeyCode::KEY_M,
+        'n' => KeyCode::KEY_N,
+        'o' => KeyCode::KEY_O,
+        'p' => KeyCode::KEY_P,
+        'q' => KeyCode::KEY_Q,
+        'r' => KeyCode::KEY_R,
+        's' => KeyCode::KEY_S,
+        't' => KeyCode::KEY_T,
+        'u' => KeyCode::KEY_U,
+        'v' => KeyCode::KEY_V,
+        'w' => KeyCode::KEY_W,
+        'x' => KeyCode::KEY_X,
+        'y' => KeyCode::KEY_Y,
+        'z' => KeyCode::KEY_Z,
+        '1' => KeyCode::KEY_1,
+        '2' => KeyCode::KEY_2,
+        '3' => KeyCode::KEY_3,
+        '4' => KeyCode::KEY_4,
+        '5' => KeyCode::KEY_5,
+        '6' => KeyCode::KEY_6,
+        '7' => KeyCode::KEY_7,
+        '8' => KeyCode::KEY_8,
+        '9' => KeyCode::KEY_9,
+        '0' => KeyCode::KEY_0,
+        ' ' => KeyCode::KEY_SPACE,
+        '\n' => KeyCode::KEY_ENTER,
+        '\t' => KeyCode::KEY_TAB,
+        '-' => KeyCode::KEY_MINUS,
+        '=' => KeyCode::KEY_EQUAL,
+        '[' => KeyCode::KEY_LEFTBRACE,
+        ']' => KeyCode::KEY_RIGHTBRACE,
+        '\\' => KeyCode::KEY_BACKSLASH,
+        ';' => KeyCode::KEY_SEMICOLON,
+        '\'' => KeyCode::KEY_APOSTROPHE,
+        '`' => KeyCode::KEY_GRAVE,
+        ',' => KeyCode::KEY_COMMA,
+        '.' => KeyCode::KEY_DOT,
+        '/' => KeyCode::KEY_SLASH,
+        _ => return shifted_symbol_key(c).map(|key| (key, true)),
+    };
+    Some((key, shift))
+}
+
+fn shifted_symbol_key(c: char) -> Option<KeyCode> {
+    let key = match c {
+        '!' => KeyCode::KEY_1,
+        '@' => KeyCode::KEY_2,
+        '#' => KeyCode::KEY_3,
+        '$' => KeyCode::KEY_4,
+        '%' => KeyCode::KEY_5,
+        '^' => KeyCode::KEY_6,
+        '&' => KeyCode::KEY_7,
+        '*' => KeyCode::KEY_8,
+        '(' => KeyCode::KEY_9,
+        ')' => KeyCode::KEY_0,
+        '_' => KeyCode::KEY_MINUS,
+        '+' => KeyCode::KEY_EQUAL,
+        '{' => KeyCode::KEY_LEFTBRACE,
+        '}' => KeyCode::KEY_RIGHTBRACE,
+        '|' => KeyCode::KEY_BACKSLASH,
+        ':' => KeyCode::KEY_SEMICOLON,
+        '"' => KeyCode::KEY_APOSTROPHE,
+        '~' => KeyCode::KEY_GRAVE,
+        '<' => KeyCode::KEY_COMMA,
+        '>' => KeyCode::KEY_DOT,
+        '?' => KeyCode::KEY_SLASH,
+        _ => return None,
+    };
+    Some(key)
+}
+
 fn run_wl_copy(wl_copy_bin: &str, wl_copy_args: &[String], text: &str) -> Result<()> {
     run_wl_copy_with_timeout(wl_copy_bin, wl_copy_args, text, Duration::from_secs(2))
 }
@@ -83,38 +349,50 @@ fn run_wl_copy_with_timeout(
     text: &str,
     timeout: Duration,
 ) -> Result<()> {
-    let mut wl_copy = Command::new(wl_copy_bin)
-        .args(wl_copy_args)
+    run_with_stdin("wl-copy", wl_copy_bin, wl_copy_args, text, timeout)
+}
+
+/// Run `bin` with `text` on stdin, failing if it exits non-zero or outlives `timeout`.
+/// `label` names the tool in error messages.
+fn run_with_stdin(
+    label: &str,
+    bin: &str,
+    args: &[String],
+    text: &str,
+    timeout: Duration,
+) -> Result<()> {
+    let mut child = Command::new(bin)
+        .args(args)
         .stdin(Stdio::piped())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .spawn()
-        .map_err(|e| WhsprError::Injection(format!("failed to spawn wl-copy: {e}")))?;
+        .map_err(|e| spawn_error(label, bin, e))?;
 
     {
         use std::io::Write;
-        let mut stdin = wl_copy
+        let mut stdin = child
             .stdin
             .take()
-            .ok_or_else(|| WhsprError::Injection("wl-copy stdin unavailable".into()))?;
+            .ok_or_else(|| WhsprError::Injection(format!("{label} stdin unavailable")))?;
         stdin
             .write_all(text.as_bytes())
-            .map_err(|e| WhsprError::Injection(format!("wl-copy stdin write: {e}")))?;
+            .map_err(|e| WhsprError::Injection(format!("{label} stdin write: {e}")))?;
     }
 
     let deadline = std::time::Instant::now() + timeout;
     let status = loop {
-        if let Some(status) = wl_copy
+        if let Some(status) = child
             .try_wait()
-            .map_err(|e| WhsprError::Injection(format!("wl-copy wait: {e}")))?
+            .map_err(|e| WhsprError::Injection(format!("{label} wait: {e}")))?
         {
             break status;
         }
         if std::time::Instant::now() >= deadline {
-            let _ = wl_copy.kill();
-            let _ = wl_copy.wait();
+            let _ = child.kill();
+            let _ = child.wait();
             return Err(WhsprError::Injection(format!(
-                "wl-copy timed out after {}ms",
+                "{label} timed out after {}ms",
                 timeout.as_millis()
             )));
         }
@@ -122,34 +400,63 @@ fn run_wl_copy_with_timeout(
     };
     if !status.success() {
         return Err(WhsprError::Injection(format!(
-            "wl-copy exited with {status}"
+            "{label} exited with {status}"
         )));
     }
     Ok(())
 }
 
-fn emit_paste_combo(device: &mut VirtualDevice) -> Result<()> {
+/// A missing binary gets an install hint rather than a bare ENOENT.
+fn spawn_error(label: &str, bin: &str, e: std::io::Error) -> WhsprError {
+    if e.kind() != std::io::ErrorKind::NotFound {
+        return WhsprError::Injection(format!("failed to spawn {label}: {e}"));
+    }
+    let package = match label {
+        "wl-copy" => "wl-clipboard",
+        other => other,
+    };
+    WhsprError::Injection(format!(
+        "{bin} not found; install {package} and make sure {label} is on PATH"
+    ))
+}
+
+/// Events for a paste combo: hold every key but the last, tap the last, then
+/// release the held keys in reverse order.
+fn paste_combo_events(paste_keys: &[KeyCode]) -> [Vec<InputEvent>; 3] {
+    let (last, held) = paste_keys
+        .split_last()
+        .expect("paste combo validated as non-empty");
+    let press = held
+        .iter()
+        .map(|key| InputEvent::new(EventType::KEY.0, key.0, 1))
+        .collect();
+    let tap = vec![
+        InputEvent::new(EventType::KEY.0, last.0, 1),
+        InputEvent::new(EventType::KEY.0, last.0, 0),
+    ];
+    let release = held
+        .iter()
+        .rev()
+        .map(|key| InputEvent::new(EventType::KEY.0, key.0, 0))
+        .collect();
+    [press, tap, release]
+}
+
+fn emit_paste_combo(device: &mut VirtualDevice, paste_keys: &[KeyCode]) -> Result<()> {
+    let [press, tap, release] = paste_combo_events(paste_keys);
+
     device
-        .emit(&[
-            InputEvent::new(EventType::KEY.0, KeyCode::KEY_LEFTCTRL.0, 1),
-            InputEvent::new(EventType::KEY.0, KeyCode::KEY_LEFTSHIFT.0, 1),
-        ])
+        .emit(&press)
         .map_err(|e| WhsprError::Injection(format!("paste modifier press: {e}")))?;
     std::thread::sleep(Duration::from_millis(12));
 
     device
-        .emit(&[
-            InputEvent::new(EventType::KEY.0, KeyCode::KEY_V.0, 1),
-            InputEvent::new(EventType::KEY.0, KeyCode::KEY_V.0, 0),
-        ])
+        .emit(&tap)
         .map_err(|e| WhsprError::Injection(format!("paste key press: {e}")))?;
     std::thread::sleep(Duration::from_millis(12));
 
     device
-        .emit(&[
-            InputEvent::new(EventType::KEY.0, KeyCode::KEY_LEFTSHIFT.0, 0),
-            InputEvent::new(EventType::KEY.0, KeyCode::KEY_LEFTCTRL.0, 0),
-        ])
+        .emit(&release)
         .map_err(|e| WhsprError::Injection(format!("paste modifier release: {e}")))?;
 
     Ok(())
@@ -161,9 +468,22 @@ mod tests {
     use crate::error::WhsprError;
 
     #[test]
-    fn run_wl_copy_reports_spawn_failure() {
+    fn run_wl_copy_suggests_installing_missing_binary() {
         let err = run_wl_copy("/definitely/missing/wl-copy", &[], "hello")
             .expect_err("missing binary should fail");
+        match err {
+            WhsprError::Injection(msg) => {
+                assert!(msg.contains("install wl-clipboard"), "unexpected: {msg}");
+            }
+            other => panic!("unexpected error variant: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn run_wl_copy_reports_other_spawn_failures() {
+        let dir = std::env::temp_dir();
+        let err = run_wl_copy(dir.to_str().unwrap(), &[], "hello")
+            .expect_err("a directory is not executable");
         match err {
             WhsprError::Injection(msg) => {
                 assert!(msg.contains("failed to spawn wl-copy"), "unexpected: {msg}");
@@ -205,9 +525,136 @@ mod tests {
         }
     }
 
+    #[test]
+    fn paste_combo_events_hold_modifiers_around_last_key() {
+        let [press, tap, release] = paste_combo_events(&[
+            KeyCode::KEY_LEFTCTRL,
+            KeyCode::KEY_LEFTSHIFT,
+            KeyCode::KEY_V,
+        ]);
+        let codes = |events: &[InputEvent]| -> Vec<(u16, i32)> {
+            events.iter().map(|e| (e.code(), e.value())).collect()
+        };
+        assert_eq!(
+            codes(&press),
+            vec![(KeyCode::KEY_LEFTCTRL.0, 1), (KeyCode::KEY_LEFTSHIFT.0, 1)]
+        );
+        assert_eq!(
+            codes(&tap),
+            vec![(KeyCode::KEY_V.0, 1), (KeyCode::KEY_V.0, 0)]
+        );
+        assert_eq!(
+            codes(&release),
+            vec![(KeyCode::KEY_LEFTSHIFT.0, 0), (KeyCode::KEY_LEFTCTRL.0, 0)]
+        );
+    }
+
+    #[test]
+    fn new_rejects_unknown_paste_key() {
+        let config = InjectConfig {
+            paste_keys: vec!["LEFTCTRL".into(), "VEE".into()],
+            ..InjectConfig::default()
+        };
+        assert!(TextInjector::new(&config).is_err());
+    }
+
+    #[test]
+    fn char_to_key_handles_case_and_shifted_symbols() {
+        assert_eq!(char_to_key('a'), Some((KeyCode::KEY_A, false)));
+        assert_eq!(char_to_key('A'), Some((KeyCode::KEY_A, true)));
+        assert_eq!(char_to_key('7'), Some((KeyCode::KEY_7, false)));
+        assert_eq!(char_to_key('?'), Some((KeyCode::KEY_SLASH, true)));
+        assert_eq!(char_to_key(' '), Some((KeyCode::KEY_SPACE, false)));
+        assert_eq!(char_to_key('é'), None);
+    }
+
+    #[test]
+    fn char_to_key_covers_printable_ascii() {
+        for c in ' '..='~' {
+            assert!(char_to_key(c).is_some(), "missing mapping for {c:?}");
+        }
+    }
+
+    #[test]
+    fn ydotool_backend_pipes_text_to_type_command() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = crate::test_support::unique_temp_dir("ydotool-backend");
+        let out = dir.join("out.txt");
+        let script = dir.join("ydotool");
+        std::fs::write(
+            &script,
+            format!("#!/bin/sh\necho \"$@\" > {0}\ncat >> {0}\n", out.display()),
+        )
+        .expect("write fake ydotool");
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))
+            .expect("make fake ydotool executable");
+
+        let backend = YdotoolBackend {
+            bin: script.to_string_lossy().into_owned(),
+        };
+        backend
+            .inject("hello")
+            .expect("fake ydotool should succeed");
+        assert_eq!(
+            std::fs::read_to_string(&out).expect("read output"),
+            "type --file -\nhello"
+        );
+    }
+
     #[tokio::test]
     async fn inject_empty_text_is_noop() {
-        let injector = TextInjector::with_wl_copy_command("/bin/true", &[]);
+        let injector = TextInjector::with_wl_copy_command(InjectMode::Paste, "/bin/true", &[]);
         injector.inject("").await.expect("empty text should no-op");
     }
+
+    #[tokio::test]
+    async fn clipboard_mode_only_runs_wl_copy() {
+        let injector = TextInjector::with_wl_copy_command(
+            InjectMode::Clipboard,
+            "/bin/sh",
+            &["-c", "cat >/dev/null"],
+        );
+        injector
+            .inject("hello")
+            .await
+            .expect("clipboard mode should not need uinput");
+    }
+
+    #[test]
+    fn copy_targets_configured_selections() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = crate::test_support::unique_temp_dir("wl-copy-selection");
+        let out = dir.join("out.txt");
+        let script = dir.join("wl-copy");
+        std::fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\necho \"[$@]\" >> {}\ncat >/dev/null\n",
+                out.display()
+            ),
+        )
+        .expect("write fake wl-copy");
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))
+            .expect("make fake wl-copy executable");
+
+        for (selection, expected) in [
+            (Selection::Clipboard, "[]\n"),
+            (Selection::Primary, "[--primary]\n"),
+            (Selection::Both, "[]\n[--primary]\n"),
+        ] {
+            let _ = std::fs::remove_file(&out);
+            let backend = UinputBackend {
+                mode: InjectMode::Paste,
+                paste_keys: vec![KeyCode::KEY_LEFTCTRL, KeyCode::KEY_V],
+                clipboard_delay: Duration::ZERO,
+                selection,
+                wl_copy_bin: script.to_string_lossy().into_owned(),
+                wl_copy_args: Vec::new(),
+            };
+            backend.copy("hello").expect("fake wl-copy should succeed");
+            assert_eq!(std::fs::read_to_string(&out).unwrap(), expected);
+        }
+    }
 }
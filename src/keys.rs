@@ -0,0 +1,164 @@
+use evdev::KeyCode;
+
+use crate::error::{Result, WhsprError};
+
+/// Key names accepted in config, without the `KEY_` prefix.
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("A", KeyCode::KEY_A),
+    ("B", KeyCode::KEY_B),
+    ("C", KeyCode::KEY_C),
+    ("D", KeyCode::KEY_D),
+    ("E", KeyCode::KEY_E),
+    ("F", KeyCode::KEY_F),
+    ("G", KeyCode::KEY_G),
+    ("H", KeyCode::KEY_H),
+    ("I", KeyCode::KEY_I),
+    ("J", KeyCode::KEY_J),
+    ("K", KeyCode::KEY_K),
+    ("L", KeyCode::KEY_L),
+    ("M", KeyCode::KEY_M),
+    ("N", KeyCode::KEY_N),
+    ("O", KeyCode::KEY_O),
+    ("P", KeyCode::KEY_P),
+    ("Q", KeyCode::KEY_Q),
+    ("R", KeyCode::KEY_R),
+    ("S", KeyCode::KEY_S),
+    ("T", KeyCode::KEY_T),
+    ("U", KeyCode::KEY_U),
+    ("V", KeyCode::KEY_V),
+    ("W", KeyCode::KEY_W),
+    ("X", KeyCode::KEY_X),
+    ("Y", KeyCode::KEY_Y),
+    ("Z", KeyCode::KEY_Z),
+    ("1", KeyCode::KEY_1),
+    ("2", KeyCode::KEY_2),
+    ("3", KeyCode::KEY_3),
+    ("4", KeyCode::KEY_4),
+    ("5", KeyCode::KEY_5),
+    ("6", KeyCode::KEY_6),
+    ("7", KeyCode::KEY_7),
+    ("8", KeyCode::KEY_8),
+    ("9", KeyCode::KEY_9),
+    ("0", KeyCode::KEY_0),
+    ("F1", KeyCode::KEY_F1),
+    ("F2", KeyCode::KEY_F2),
+    ("F3", KeyCode::KEY_F3),
+    ("F4", KeyCode::KEY_F4),
+    ("F5", KeyCode::KEY_F5),
+    ("F6", KeyCode::KEY_F6),
+    ("F7", KeyCode::KEY_F7),
+    ("F8", KeyCode::KEY_F8),
+    ("F9", KeyCode::KEY_F9),
+    ("F10", KeyCode::KEY_F10),
+    ("F11", KeyCode::KEY_F11),
+    ("F12", KeyCode::KEY_F12),
+    ("LEFTCTRL", KeyCode::KEY_LEFTCTRL),
+    ("RIGHTCTRL", KeyCode::KEY_RIGHTCTRL),
+    ("LEFTSHIFT", KeyCode::KEY_LEFTSHIFT),
+    ("RIGHTSHIFT", KeyCode::KEY_RIGHTSHIFT),
+    ("LEFTALT", KeyCode::KEY_LEFTALT),
+    ("RIGHTALT", KeyCode::KEY_RIGHTALT),
+    ("LEFTMETA", KeyCode::KEY_LEFTMETA),
+    ("RIGHTMETA", KeyCode::KEY_RIGHTMETA),
+    ("SPACE", KeyCode::KEY_SPACE),
+    ("ENTER", KeyCode::KEY_ENTER),
+    ("TAB", KeyCode::KEY_TAB),
+    ("ESC", KeyCode::KEY_ESC),
+    ("BACKSPACE", KeyCode::KEY_BACKSPACE),
+    ("INSERT", KeyCode::KEY_INSERT),
+    ("DELETE", KeyCode::KEY_DELETE),
+    ("HOME", KeyCode::KEY_HOME),
+    ("END", KeyCode::KEY_END),
+    ("PAGEUP", KeyCode::KEY_PAGEUP),
+    ("PAGEDOWN", KeyCode::KEY_PAGEDOWN),
+    ("UP", KeyCode::KEY_UP),
+    ("DOWN", KeyCode::KEY_DOWN),
+    ("LEFT", KeyCode::KEY_LEFT),
+    ("RIGHT", KeyCode::KEY_RIGHT),
+    ("MINUS", KeyCode::KEY_MINUS),
+    ("EQUAL", KeyCode::KEY_EQUAL),
+    ("GRAVE", KeyCode::KEY_GRAVE),
+    ("LEFTBRACE", KeyCode::KEY_LEFTBRACE),
+    ("RIGHTBRACE", KeyCode::KEY_RIGHTBRACE),
+    ("SEMICOLON", KeyCode::KEY_SEMICOLON),
+    ("BACKSLASH", KeyCode::KEY_BACKSLASH),
+];
+
+/// Parse a key name like `LEFTCTRL` or `KEY_V` (case-insensitive).
+pub fn parse_key_name(name: &str) -> Option<KeyCode> {
+    let upper = name.trim().to_ascii_uppercase();
+    let bare = upper.strip_prefix("KEY_").unwrap_or(&upper);
+    KEY_NAMES
+        .iter()
+        .find(|(known, _)| *known == bare)
+        .map(|(_, key)| *key)
+}
+
+/// Parse a key combo, failing with the list of valid names on the first unknown key.
+pub fn parse_key_combo(names: &[String]) -> Result<Vec<KeyCode>> {
+    if names.is_empty() {
+        return Err(WhsprError::Config("key combo must not be empty".into()));
+    }
+    names
+        .iter()
+        .map(|name| {
+            parse_key_name(name).ok_or_else(|| {
+                let valid: Vec<&str> = KEY_NAMES.iter().map(|(known, _)| *known).collect();
+                WhsprError::Config(format!(
+                    "unknown key name '{name}'. Valid names: {}",
+                    valid.join(", ")
+                ))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_key_name_accepts_prefix_and_any_case() {
+        assert_eq!(parse_key_name("LEFTCTRL"), Some(KeyCode::KEY_LEFTCTRL));
+        assert_eq!(parse_key_name("key_v"), Some(KeyCode::KEY_V));
+        assert_eq!(parse_key_name("f12"), Some(KeyCode::KEY_F12));
+        assert_eq!(parse_key_name("NOPE"), None);
+    }
+
+    #[test]
+    fn parse_key_name_covers_digits_punctuation_and_arrows() {
+        assert_eq!(parse_key_name("1"), Some(KeyCode::KEY_1));
+        assert_eq!(parse_key_name("KEY_0"), Some(KeyCode::KEY_0));
+        assert_eq!(parse_key_name("minus"), Some(KeyCode::KEY_MINUS));
+        assert_eq!(parse_key_name("EQUAL"), Some(KeyCode::KEY_EQUAL));
+        assert_eq!(parse_key_name("grave"), Some(KeyCode::KEY_GRAVE));
+        assert_eq!(parse_key_name("LEFTBRACE"), Some(KeyCode::KEY_LEFTBRACE));
+        assert_eq!(parse_key_name("RIGHTBRACE"), Some(KeyCode::KEY_RIGHTBRACE));
+        assert_eq!(parse_key_name("SEMICOLON"), Some(KeyCode::KEY_SEMICOLON));
+        assert_eq!(parse_key_name("BACKSLASH"), Some(KeyCode::KEY_BACKSLASH));
+        assert_eq!(parse_key_name("up"), Some(KeyCode::KEY_UP));
+        assert_eq!(parse_key_name("KEY_DOWN"), Some(KeyCode::KEY_DOWN));
+        assert_eq!(parse_key_name("LEFT"), Some(KeyCode::KEY_LEFT));
+        assert_eq!(parse_key_name("RIGHT"), Some(KeyCode::KEY_RIGHT));
+    }
+
+    #[test]
+    fn parse_key_combo_reports_unknown_names() {
+        let err = parse_key_combo(&["LEFTCTRL".into(), "VV".into()]).expect_err("should fail");
+        match err {
+            WhsprError::Config(msg) => {
+                assert!(msg.contains("unknown key name 'VV'"), "unexpected: {msg}");
+                assert!(
+                    msg.contains("LEFTCTRL"),
+                    "valid names should be listed: {msg}"
+                );
+            }
+            other => panic!("unexpected error variant: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_key_combo_rejects_empty_combo() {
+        assert!(parse_key_combo(&[]).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+//! The live input level, published by whspr-rs through a small memory-mapped
+//! runtime file so whspr-osd can draw it without opening the mic itself.
+
+use std::fs::OpenOptions;
+use std::io;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Recent samples kept for the waveform view.
+pub const METER_SAMPLES: usize = 2048;
+
+/// RMS of the latest block, then the ring's write position, then the ring.
+const HEADER_WORDS: usize = 2;
+const WORDS: usize = HEADER_WORDS + METER_SAMPLES;
+const LEN_BYTES: usize = WORDS * 4;
+
+pub fn meter_path() -> PathBuf {
+    crate::runtime::runtime_file("whspr-rs.level")
+}
+
+/// A shared mapping of the meter file. Every field is an atomic, so the
+/// writer never blocks on the reader.
+pub struct LevelMeter {
+    words: NonNull<AtomicU32>,
+}
+
+// The mapping is only accessed through atomics.
+unsafe impl Send for LevelMeter {}
+unsafe impl Sync for LevelMeter {}
+
+impl LevelMeter {
+    /// Create the meter file, or reset an existing one, for writing.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        // No truncate: an overlay may still have the old file mapped, and
+        // shrinking it under that mapping would crash the overlay.
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .mode(0o600)
+            .open(path)?;
+        file.set_len(LEN_BYTES as u64)?;
+        let meter = Self::map(&file)?;
+        for word in meter.words() {
+            word.store(0, Ordering::Relaxed);
+        }
+        Ok(meter)
+    }
+
+    /// Map the meter of a running whspr-rs.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        if file.metadata()?.len() < LEN_BYTES as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "level meter file is too short",
+            ));
+        }
+        Self::map(&file)
+    }
+
+    fn map(file: &std::fs::File) -> io::Result<Self> {
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                LEN_BYTES,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        let words = NonNull::new(ptr.cast()).ok_or_else(io::Error::last_os_error)?;
+        Ok(Self { words })
+    }
+
+    fn words(&self) -> &[AtomicU32] {
+        unsafe { std::slice::from_raw_parts(self.words.as_ptr(), WORDS) }
+    }
+
+    /// Append a block of mono samples and publish its RMS.
+    pub fn push(&self, block: &[f32]) {
+        if block.is_empty() {
+            return;
+        }
+        let words = self.words();
+        let mut pos = words[1].load(Ordering::Relaxed);
+        let mut sum = 0.0f32;
+        for &sample in block {
+            words[HEADER_WORDS + pos as usize % METER_SAMPLES]
+                .store(sample.to_bits(), Ordering::Relaxed);
+            pos = pos.wrapping_add(1);
+            sum += sample * sample;
+        }
+        words[1].store(pos, Ordering::Relaxed);
+        let rms = (sum / block.len() as f32).sqrt();
+        words[0].store(rms.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn rms(&self) -> f32 {
+        f32::from_bits(self.words()[0].load(Ordering::Relaxed))
+    }
+
+    /// Copy the ring into `out`, oldest sample first.
+    pub fn snapshot(&self, out: &mut [f32; METER_SAMPLES]) {
+        let words = self.words();
+        let start = words[1].load(Ordering::Relaxed) as usize;
+        for (i, slot) in out.iter_mut().enumerate() {
+            let bits = words[HEADER_WORDS + (start + i) % METER_SAMPLES].load(Ordering::Relaxed);
+            *slot = f32::from_bits(bits);
+        }
+    }
+}
+
+impl Drop for LevelMeter {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.words.as_ptr().cast(), LEN_BYTES);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_sees_what_writer_pushes() {
+        let path =
+            std::env::temp_dir().join(format!("whspr-rs-test-level-{}.level", std::process::id()));
+        let writer = LevelMeter::create(&path).unwrap();
+        let reader = LevelMeter::open(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        writer.push(&[0.5, -0.5, 0.5, -0.5]);
+        assert!((reader.rms() - 0.5).abs() < 1e-6);
+
+        let mut out = [0.0; METER_SAMPLES];
+        reader.snapshot(&mut out);
+        assert_eq!(&out[METER_SAMPLES - 4..], &[0.5, -0.5, 0.5, -0.5]);
+        assert!(out[..METER_SAMPLES - 4].iter().all(|&s| s == 0.0));
+    }
+}
@@ -0,0 +1,41 @@
+//! The dictation pipeline behind the `whspr-rs` binary, for use from other
+//! programs: record or decode audio, transcribe it with a local Whisper
+//! model, and type the result into the focused window.
+//!
+//! ```no_run
+//! use whspr_rs::{Config, TextInjector, TranscriptionBackend, WhisperLocal};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> whspr_rs::Result<()> {
+//! let config = Config::load(None)?;
+//! let whisper = WhisperLocal::new(&config.whisper, &config.resolved_model_path())?;
+//! let audio = whspr_rs::decode_audio_file("note.ogg".as_ref())?;
+//! let text = whisper.transcribe(&audio, whspr_rs::file_audio::TARGET_SAMPLE_RATE)?;
+//! TextInjector::new(&config.inject)?.inject(&text).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod audio;
+pub mod config;
+pub mod error;
+pub mod file_audio;
+pub mod inject;
+// Shared with the whspr-rs and whspr-osd binaries; not part of the API.
+#[doc(hidden)]
+pub mod keys;
+#[doc(hidden)]
+pub mod level_meter;
+pub mod osd_config;
+#[doc(hidden)]
+pub mod runtime;
+#[cfg(test)]
+mod test_support;
+pub mod transcribe;
+
+pub use audio::AudioRecorder;
+pub use config::Config;
+pub use error::{Result, WhsprError};
+pub use file_audio::decode_audio_file;
+pub use inject::TextInjector;
+pub use transcribe::{TranscriptionBackend, WhisperLocal};
@@ -1,26 +1,31 @@
 mod app;
-mod audio;
 mod cli;
-mod config;
-mod error;
+mod doctor;
 mod feedback;
-mod file_audio;
-mod inject;
+mod focus;
 mod model;
+mod output;
+mod postprocess;
+mod replace;
 mod setup;
+mod status_socket;
 #[cfg(test)]
 mod test_support;
-mod transcribe;
+mod validate;
 
 use std::path::{Path, PathBuf};
 
 use clap::Parser;
+use cpal::traits::{DeviceTrait, HostTrait};
+use indicatif::{ProgressBar, ProgressStyle};
 use tracing_subscriber::EnvFilter;
+use whspr_rs::config::{Config, InjectBackendKind, InjectMode};
+use whspr_rs::error::WhsprError;
+use whspr_rs::transcribe::WhisperLocal;
+use whspr_rs::{audio, config, file_audio, runtime, transcribe};
 
-use crate::cli::{Cli, Command, ModelAction};
-use crate::config::Config;
-use crate::error::WhsprError;
-use crate::transcribe::{TranscriptionBackend, WhisperLocal};
+use crate::cli::{Cli, Command, ConfigAction, ModelAction};
+use crate::output::OutputFormat;
 
 struct PidLock {
     path: PathBuf,
@@ -34,8 +39,7 @@ impl Drop for PidLock {
 }
 
 fn pid_file_path() -> PathBuf {
-    let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".into());
-    PathBuf::from(runtime_dir).join("whspr-rs.pid")
+    runtime::runtime_file("whspr-rs.pid")
 }
 
 fn read_pid_from_lock(path: &Path) -> Option<libc::pid_t> {
@@ -43,8 +47,18 @@ fn read_pid_from_lock(path: &Path) -> Option<libc::pid_t> {
     contents.trim().parse().ok()
 }
 
+/// Whether `pid` is a live process. A zombie, e.g. a crashed instance whose
+/// parent hasn't reaped it, keeps its /proc entry but no longer runs.
 fn process_exists(pid: libc::pid_t) -> bool {
-    Path::new(&format!("/proc/{pid}")).exists()
+    std::fs::read_to_string(format!("/proc/{pid}/stat"))
+        .is_ok_and(|stat| !matches!(process_state(&stat), Some('Z' | 'X') | None))
+}
+
+/// The state letter from `/proc/<pid>/stat`. It follows the command name,
+/// which is in parentheses and may itself contain spaces or parentheses.
+fn process_state(stat: &str) -> Option<char> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    rest.trim_start().chars().next()
 }
 
 fn pid_belongs_to_whspr(pid: libc::pid_t) -> bool {
@@ -99,7 +113,7 @@ fn try_acquire_pid_lock(path: &Path) -> std::io::Result<PidLock> {
     })
 }
 
-fn signal_existing_instance(path: &Path) -> crate::error::Result<bool> {
+fn signal_existing_instance(path: &Path) -> whspr_rs::error::Result<bool> {
     let Some(pid) = read_pid_from_lock(path) else {
         tracing::warn!("stale pid lock at {}, removing", path.display());
         let _ = std::fs::remove_file(path);
@@ -108,7 +122,7 @@ fn signal_existing_instance(path: &Path) -> crate::error::Result<bool> {
 
     if !pid_belongs_to_whspr(pid) {
         tracing::warn!(
-            "pid lock at {} points to non-whspr process ({pid}), removing",
+            "pid lock at {} points to {pid}, which is not a running whspr-rs, removing",
             path.display()
         );
         let _ = std::fs::remove_file(path);
@@ -131,7 +145,7 @@ fn signal_existing_instance(path: &Path) -> crate::error::Result<bool> {
     Err(err.into())
 }
 
-fn acquire_or_signal_lock() -> crate::error::Result<Option<PidLock>> {
+fn acquire_or_signal_lock() -> whspr_rs::error::Result<Option<PidLock>> {
     let path = pid_file_path();
 
     for _ in 0..2 {
@@ -146,12 +160,134 @@ fn acquire_or_signal_lock() -> crate::error::Result<Option<PidLock>> {
         }
     }
 
-    Err(crate::error::WhsprError::Config(format!(
+    Err(whspr_rs::error::WhsprError::Config(format!(
         "failed to acquire pid lock at {}",
         path.display()
     )))
 }
 
+/// The pid lock if no live instance holds it; `None` means one does. Never
+/// signals that instance.
+fn acquire_lock_if_free() -> whspr_rs::error::Result<Option<PidLock>> {
+    let path = pid_file_path();
+    for _ in 0..2 {
+        match try_acquire_pid_lock(&path) {
+            Ok(lock) => return Ok(Some(lock)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                if read_pid_from_lock(&path).is_some_and(pid_belongs_to_whspr) {
+                    return Ok(None);
+                }
+                tracing::warn!("stale pid lock at {}, removing", path.display());
+                let _ = std::fs::remove_file(&path);
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(None)
+}
+
+fn print_status() {
+    let path = pid_file_path();
+    let Some(pid) = read_pid_from_lock(&path) else {
+        println!("not running");
+        return;
+    };
+    if !pid_belongs_to_whspr(pid) {
+        println!("not running (stale pid lock at {})", path.display());
+        return;
+    }
+
+    let state = std::fs::read_to_string(app::state_file_path())
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|_| "unknown".into());
+    println!("running (pid {pid}): {state}");
+}
+
+/// Ask a running instance to drop its recording. Only a recording can be
+/// cancelled; once transcription has started, SIGINT lets it finish instead.
+fn cancel_recording() -> whspr_rs::error::Result<()> {
+    let path = pid_file_path();
+    let Some(pid) = read_pid_from_lock(&path).filter(|&pid| pid_belongs_to_whspr(pid)) else {
+        println!("not running, nothing to cancel");
+        return Ok(());
+    };
+    let state = std::fs::read_to_string(app::state_file_path()).unwrap_or_default();
+    if state.trim() != app::AppState::Recording.as_str() {
+        println!("not recording, nothing to cancel");
+        return Ok(());
+    }
+
+    tracing::info!("sending cancel signal to running instance (pid {pid})");
+    if unsafe { libc::kill(pid, libc::SIGINT) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+fn kill_instance() -> whspr_rs::error::Result<()> {
+    let path = pid_file_path();
+    let Some(pid) = read_pid_from_lock(&path).filter(|&pid| pid_belongs_to_whspr(pid)) else {
+        println!("not running");
+        return Ok(());
+    };
+
+    tracing::info!("sending terminate signal to running instance (pid {pid})");
+    if unsafe { libc::kill(pid, libc::SIGTERM) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    println!("terminating whspr-rs (pid {pid})");
+    Ok(())
+}
+
+/// Print the input devices for `whspr-rs devices`, marking the default.
+fn list_input_devices() -> whspr_rs::error::Result<()> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().map(|d| audio::device_name(&d));
+    let devices = host
+        .input_devices()
+        .map_err(|e| WhsprError::Audio(format!("failed to enumerate input devices: {e}")))?;
+
+    println!(
+        "{:>4}  {:<38} {:>8}  {:>8}  FORMATS",
+        "#", "DEVICE", "RATE", "CHANNELS"
+    );
+    println!("{}", "-".repeat(84));
+    for (index, device) in devices.enumerate() {
+        let name = audio::device_name(&device);
+        let marker = if default_name.as_deref() == Some(name.as_str()) {
+            "* "
+        } else {
+            "  "
+        };
+        let (rate, channels) = match device.default_input_config() {
+            Ok(cfg) => (
+                format!("{} Hz", cfg.sample_rate()),
+                cfg.channels().to_string(),
+            ),
+            Err(_) => ("?".to_string(), "?".to_string()),
+        };
+        let mut formats: Vec<String> = Vec::new();
+        if let Ok(configs) = device.supported_input_configs() {
+            for cfg in configs {
+                let format = format!("{:?}", cfg.sample_format());
+                if !formats.contains(&format) {
+                    formats.push(format);
+                }
+            }
+        }
+        println!(
+            "{}{:>2}  {:<38} {:>8}  {:>8}  {}",
+            marker,
+            index,
+            name,
+            rate,
+            channels,
+            formats.join(", ")
+        );
+    }
+    Ok(())
+}
+
 fn init_tracing(verbose: u8) {
     let filter = match verbose {
         0 => "whspr_rs=info",
@@ -167,54 +303,174 @@ fn init_tracing(verbose: u8) {
         .init();
 }
 
+fn load_config(cli: &Cli) -> whspr_rs::error::Result<Config> {
+    let mut config = Config::load(cli.config.as_deref())?;
+    config.apply_env_overrides()?;
+    Ok(config)
+}
+
+fn chunk_progress_bar(total_chunks: usize) -> ProgressBar {
+    let pb = ProgressBar::new(total_chunks as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{bar:40.cyan/blue}] chunk {pos}/{len} ({eta})")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+    pb
+}
+
+/// Where `transcribe` writes its result.
+enum Destination<'a> {
+    Stdout,
+    File(&'a Path),
+    /// A file in `dir` named by expanding `template`.
+    Dir {
+        dir: &'a Path,
+        template: &'a str,
+    },
+}
+
+/// `transcribe` flags that override `[whisper]` settings for one run.
+struct TranscribeOverrides {
+    model: Option<String>,
+    no_verify: bool,
+    language: Option<String>,
+    translate: bool,
+    word_timestamps: bool,
+}
+
 async fn transcribe_file(
     cli: &Cli,
     file: &Path,
-    output: Option<&Path>,
-) -> crate::error::Result<()> {
-    let config = Config::load(cli.config.as_deref())?;
-    let model_path = config.resolved_model_path();
+    destination: Destination<'_>,
+    format: OutputFormat,
+    overrides: TranscribeOverrides,
+    quiet: bool,
+) -> whspr_rs::error::Result<()> {
+    if let Destination::Dir { dir, .. } = destination
+        && !dir.is_dir()
+    {
+        return Err(WhsprError::Config(format!(
+            "output directory {} does not exist",
+            dir.display()
+        )));
+    }
+
+    let mut config = load_config(cli)?;
+    if let Some(language) = overrides.language {
+        transcribe::check_language(&language)?;
+        config.whisper.language = language;
+    }
+    if overrides.translate {
+        config.whisper.translate = true;
+    }
+    if overrides.word_timestamps {
+        config.whisper.word_timestamps = true;
+    }
+    let model_path = match &overrides.model {
+        Some(name) => {
+            model::ensure_model(name, &config.model.base_url, !overrides.no_verify).await?
+        }
+        None => config.resolved_model_path(),
+    };
 
     tracing::info!("decoding audio file: {}", file.display());
-    let samples = file_audio::decode_audio_file(file)?;
+    let from_stdin = file == Path::new("-");
+    let samples = if from_stdin {
+        file_audio::decode_stdin()?
+    } else {
+        file_audio::decode_audio_file(file)?
+    };
 
     let backend =
         tokio::task::spawn_blocking(move || WhisperLocal::new(&config.whisper, &model_path))
             .await
             .map_err(|e| WhsprError::Transcription(format!("model loading task failed: {e}")))??;
 
-    let text = tokio::task::spawn_blocking(move || {
-        backend.transcribe(&samples, file_audio::TARGET_SAMPLE_RATE)
+    let transcript = tokio::task::spawn_blocking(move || {
+        // The bar draws on stderr, so it never mixes into a transcript on stdout.
+        let bar = std::cell::OnceCell::new();
+        let result = backend.transcribe_with_progress(
+            &samples,
+            file_audio::TARGET_SAMPLE_RATE,
+            |done, total| {
+                if !quiet {
+                    bar.get_or_init(|| chunk_progress_bar(total))
+                        .set_position(done as u64);
+                }
+            },
+        );
+        if let Some(bar) = bar.get() {
+            bar.finish_and_clear();
+        }
+        result
     })
     .await
     .map_err(|e| WhsprError::Transcription(format!("transcription task failed: {e}")))??;
 
-    if let Some(out_path) = output {
-        tokio::fs::write(out_path, &text).await?;
-        tracing::info!("transcription written to {}", out_path.display());
-    } else {
-        println!("{text}");
-    }
+    let text = output::render(format, &transcript);
 
+    let out_path = match destination {
+        Destination::Stdout => {
+            println!("{text}");
+            return Ok(());
+        }
+        Destination::File(path) => path.to_path_buf(),
+        Destination::Dir { dir, template } => {
+            let stem = if from_stdin {
+                "stdin".into()
+            } else {
+                file.file_stem()
+                    .map(|s| s.to_string_lossy())
+                    .unwrap_or_default()
+            };
+            dir.join(output::file_name(
+                template,
+                &stem,
+                format,
+                &transcript.language,
+            ))
+        }
+    };
+    tokio::fs::write(&out_path, &text).await?;
+    tracing::info!("transcription written to {}", out_path.display());
     Ok(())
 }
 
-async fn run_default(cli: &Cli) -> crate::error::Result<()> {
-    let Some(_pid_lock) = acquire_or_signal_lock()? else {
-        return Ok(());
+async fn run_default(cli: &Cli) -> whspr_rs::error::Result<()> {
+    let pid_lock = if cli.no_signal {
+        acquire_lock_if_free()?
+    } else {
+        let Some(lock) = acquire_or_signal_lock()? else {
+            return Ok(());
+        };
+        Some(lock)
     };
 
     tracing::info!("whspr-rs v{}", env!("CARGO_PKG_VERSION"));
+    let standalone = pid_lock.is_none();
+    if standalone {
+        let pid = std::process::id();
+        tracing::info!(
+            "another instance holds the pid lock, running standalone; stop with `kill -USR2 {pid}`"
+        );
+    }
 
     // Load config
-    let config = Config::load(cli.config.as_deref())?;
+    let mut config = load_config(cli)?;
+    if cli.no_inject {
+        // Dry run: only wl-copy, never synthesize keystrokes.
+        config.inject.backend = InjectBackendKind::Uinput;
+        config.inject.mode = InjectMode::Clipboard;
+    }
     tracing::debug!("config loaded: {config:?}");
 
-    app::run(config).await
+    app::run(config, cli.no_inject, standalone).await
 }
 
 #[tokio::main]
-async fn main() -> crate::error::Result<()> {
+async fn main() -> whspr_rs::error::Result<()> {
     let cli = Cli::parse();
 
     init_tracing(cli.verbose);
@@ -222,19 +478,91 @@ async fn main() -> crate::error::Result<()> {
     match &cli.command {
         None => run_default(&cli).await,
         Some(Command::Setup) => setup::run_setup(cli.config.as_deref()).await,
-        Some(Command::Transcribe { file, output }) => {
-            transcribe_file(&cli, file, output.as_deref()).await
+        Some(Command::Transcribe {
+            file,
+            output,
+            output_dir,
+            name,
+            format,
+            model,
+            no_verify,
+            language,
+            translate,
+            word_timestamps,
+            quiet,
+            list_formats,
+        }) => {
+            let Some(file) = file.as_deref().filter(|_| !*list_formats) else {
+                print!("{}", output::format_list());
+                return Ok(());
+            };
+            let destination = match (output, output_dir) {
+                (Some(path), _) => Destination::File(path),
+                (None, Some(dir)) => Destination::Dir {
+                    dir,
+                    template: name,
+                },
+                (None, None) => Destination::Stdout,
+            };
+            transcribe_file(
+                &cli,
+                file,
+                destination,
+                *format,
+                TranscribeOverrides {
+                    model: model.clone(),
+                    no_verify: *no_verify,
+                    language: language.clone(),
+                    translate: *translate,
+                    word_timestamps: *word_timestamps,
+                },
+                *quiet,
+            )
+            .await
+        }
+        Some(Command::Status) => {
+            print_status();
+            Ok(())
         }
+        Some(Command::Cancel) => cancel_recording(),
+        Some(Command::Kill) => kill_instance(),
+        Some(Command::Devices) => list_input_devices(),
+        Some(Command::Doctor { selftest }) => doctor::run_doctor(cli.config.as_deref(), *selftest),
         Some(Command::Model { action }) => match action {
             ModelAction::List => {
                 model::list_models(cli.config.as_deref());
                 Ok(())
             }
-            ModelAction::Download { name } => {
-                model::download_model(name).await?;
+            ModelAction::Download {
+                name,
+                no_verify,
+                retries,
+            } => {
+                model::download_model(name, cli.config.as_deref(), !*no_verify, *retries).await?;
+                Ok(())
+            }
+            ModelAction::Select { name, path } => match (name, path) {
+                (_, Some(path)) => model::select_model_path(path, cli.config.as_deref()),
+                (Some(name), None) => model::select_model(name, cli.config.as_deref()),
+                (None, None) => unreachable!("clap requires a name or --path"),
+            },
+            ModelAction::Info { name } => model::model_info(name, cli.config.as_deref()),
+            ModelAction::Delete { name } => model::delete_model(name, cli.config.as_deref()),
+        },
+        Some(Command::Config { action }) => match action {
+            ConfigAction::Validate => validate::validate_config(cli.config.as_deref()),
+            ConfigAction::Path => {
+                println!(
+                    "{}",
+                    config::resolve_config_path(cli.config.as_deref()).display()
+                );
+                Ok(())
+            }
+            ConfigAction::Show => {
+                let config = load_config(&cli)?;
+                print!("{}", config.to_toml()?);
                 Ok(())
             }
-            ModelAction::Select { name } => model::select_model(name, cli.config.as_deref()),
         },
     }
 }
@@ -254,6 +582,14 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn process_state_reads_state_after_command_name() {
+        assert_eq!(process_state("1234 (whspr-rs) S 1 1234"), Some('S'));
+        assert_eq!(process_state("1234 (odd) name)) Z 1 1234"), Some('Z'));
+        assert_eq!(process_state("garbage"), None);
+        assert!(process_exists(std::process::id() as libc::pid_t));
+    }
+
     #[test]
     fn signal_existing_instance_cleans_invalid_pid_file() {
         let path = temp_lock_path("invalid");
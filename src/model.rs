@@ -1,19 +1,22 @@
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
 
 use futures_util::StreamExt;
 use indicatif::{ProgressBar, ProgressStyle};
+use sha2::{Digest, Sha256};
 use tokio::io::AsyncWriteExt;
 
-use crate::config::{self, data_dir, resolve_config_path, update_config_model_path};
-use crate::error::{Result, WhsprError};
-
-const MODEL_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+use whspr_rs::config::{self, data_dir, resolve_config_path, update_config_model_path};
+use whspr_rs::error::{Result, WhsprError};
 
 pub struct ModelInfo {
     pub name: &'static str,
     pub filename: &'static str,
     pub size: &'static str,
     pub description: &'static str,
+    /// Expected SHA-256 of the model file, checked after every download.
+    pub sha256: &'static str,
 }
 
 pub const MODELS: &[ModelInfo] = &[
@@ -22,72 +25,84 @@ pub const MODELS: &[ModelInfo] = &[
         filename: "ggml-large-v3-turbo.bin",
         size: "1.6 GB",
         description: "Best balance of speed and accuracy (recommended)",
+        sha256: "1fc70f774d38eb169993ac391eea357ef47c88757ef72ee5943879b7e8e2bc69",
     },
     ModelInfo {
         name: "large-v3-turbo-q5_0",
         filename: "ggml-large-v3-turbo-q5_0.bin",
         size: "574 MB",
         description: "Quantized turbo, smaller and slightly less accurate",
+        sha256: "394221709cd5ad1f40c46e6031ca61bce88931e6e088c188294c6d5a55ffa7e2",
     },
     ModelInfo {
         name: "large-v3",
         filename: "ggml-large-v3.bin",
         size: "3.1 GB",
         description: "Most accurate, significantly slower",
+        sha256: "64d182b440b98d5203c4f9bd541544d84c605196c4f7b845dfa11fb23594d1e2",
     },
     ModelInfo {
         name: "large-v3-q5_0",
         filename: "ggml-large-v3-q5_0.bin",
         size: "1.1 GB",
         description: "Quantized large, good accuracy/size tradeoff",
+        sha256: "d75795ecff3f83b5faa89d1900604ad8c780abd5739fae406de19f23ecd98ad1",
     },
     ModelInfo {
         name: "medium",
         filename: "ggml-medium.bin",
         size: "1.5 GB",
         description: "Medium model",
+        sha256: "6c14d5adee5f86394037b4e4e8b59f1673b6cee10e3cf0b11bbdbee79c156208",
     },
     ModelInfo {
         name: "medium.en",
         filename: "ggml-medium.en.bin",
         size: "1.5 GB",
         description: "Medium model, English only",
+        sha256: "cc37e93478338ec7700281a7ac30a10128929eb8f427dda2e865faa8f6da4356",
     },
     ModelInfo {
         name: "small",
         filename: "ggml-small.bin",
         size: "488 MB",
         description: "Small model, fast",
+        sha256: "1be3a9b2063867b937e64e2ec7483364a79917e157fa98c5d94b5c1fffea987b",
     },
     ModelInfo {
         name: "small.en",
         filename: "ggml-small.en.bin",
         size: "488 MB",
         description: "Small model, English only",
+        sha256: "c6138d6d58ecc8322097e0f987c32f1be8bb0a18532a3f88f734d1bbf9c41e5d",
     },
     ModelInfo {
         name: "base",
         filename: "ggml-base.bin",
         size: "148 MB",
         description: "Base model, very fast",
+        sha256: "60ed5bc3dd14eea856493d334349b405782ddcaf0028d4b5df4088345fba2efe",
     },
     ModelInfo {
         name: "base.en",
         filename: "ggml-base.en.bin",
         size: "148 MB",
         description: "Base model, English only",
+        sha256: "a03779c86df3323075f5e796cb2ce5029f00ec8869eee3fdfb897afe36c6d002",
     },
     ModelInfo {
         name: "tiny",
         filename: "ggml-tiny.bin",
         size: "78 MB",
         description: "Tiny model, fastest, least accurate",
+        sha256: "be07e048e1e599ad46341c8d2a135645097a538221678b7acdd1b1919c6e1b21",
     },
     ModelInfo {
         name: "tiny.en",
         filename: "ggml-tiny.en.bin",
         size: "78 MB",
         description: "Tiny model, English only",
+        sha256: "921e4cf8686fdd993dcd081a5da5b6c365bfde1162e72b08d75ac75289920b1f",
     },
 ];
 
@@ -156,6 +171,23 @@ pub fn list_models(config_path_override: Option<&Path>) {
             marker, m.name, m.size, status, m.description
         );
     }
+    if let Some(active) = active_resolved.as_deref().filter(|p| is_custom_model(p)) {
+        let size = std::fs::metadata(active)
+            .map(|m| format_size(m.len()))
+            .unwrap_or_else(|_| "-".into());
+        println!(
+            "* {:<20} {:>8}  {:<8}  {}",
+            "custom",
+            size,
+            "active",
+            active.display()
+        );
+    }
+}
+
+/// Whether `path` is outside the built-in model list.
+fn is_custom_model(path: &Path) -> bool {
+    !MODELS.iter().any(|m| model_path(m.filename) == path)
 }
 
 fn validated_existing_len(existing_len: u64, status: reqwest::StatusCode) -> Result<u64> {
@@ -178,66 +210,83 @@ fn validated_existing_len(existing_len: u64, status: reqwest::StatusCode) -> Res
     }
 }
 
-pub async fn download_model(name: &str) -> Result<PathBuf> {
-    download_model_from_base(name, MODEL_BASE_URL).await
+fn file_sha256(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| WhsprError::Download(format!("failed to open download for hashing: {e}")))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)
+        .map_err(|e| WhsprError::Download(format!("failed to hash download: {e}")))?;
+    Ok(format!("{:x}", hasher.finalize()))
 }
 
-pub(crate) async fn download_model_from_base(name: &str, base_url: &str) -> Result<PathBuf> {
-    let info = find_model(name).ok_or_else(|| {
-        let available: Vec<&str> = MODELS.iter().map(|m| m.name).collect();
-        WhsprError::Download(format!(
-            "unknown model '{}'. Available: {}",
-            name,
-            available.join(", ")
-        ))
-    })?;
-
-    let dest = model_path(info.filename);
-    let part_path = dest.with_extension("bin.part");
-
-    if dest.exists() {
-        tracing::info!("model '{name}' already downloaded at {}", dest.display());
-        println!("Model '{}' already downloaded at {}", name, dest.display());
-        return Ok(dest);
-    }
-
-    // Ensure data directory exists
-    if let Some(parent) = dest.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| WhsprError::Download(format!("failed to create data directory: {e}")))?;
+/// Compare the SHA-256 of `path` against `expected`, deleting the file on mismatch.
+fn verify_checksum(path: &Path, expected: &str) -> Result<()> {
+    let actual = file_sha256(path)?;
+    if actual.eq_ignore_ascii_case(expected) {
+        return Ok(());
     }
+    let _ = std::fs::remove_file(path);
+    Err(WhsprError::Download(format!(
+        "checksum mismatch for {}: expected sha256 {expected}, got {actual}. \
+         The partial download was removed; retry the download, or pass --no-verify if you use a mirror with different files",
+        path.display()
+    )))
+}
 
-    let url = format!("{}/{}", base_url.trim_end_matches('/'), info.filename);
-    tracing::info!("downloading model '{}' from {}", info.name, url);
+/// Why a download attempt stopped. Only network trouble is worth retrying;
+/// an HTTP error or a local I/O failure would just fail again.
+enum FetchError {
+    Transient(WhsprError),
+    Fatal(WhsprError),
+}
 
-    println!("Downloading {} ({})...", info.name, info.size);
+/// Wait before retry number `attempt` (1-based): 1s, 2s, 4s, ... up to 32s.
+fn retry_delay(attempt: u32) -> std::time::Duration {
+    std::time::Duration::from_secs(1 << attempt.saturating_sub(1).min(5))
+}
 
-    let client = reqwest::Client::new();
+/// One attempt at fetching `url` into `part_path`, resuming from whatever it
+/// already holds.
+async fn fetch_to_part(
+    client: &reqwest::Client,
+    url: &str,
+    part_path: &Path,
+    pb: &ProgressBar,
+) -> std::result::Result<(), FetchError> {
+    use FetchError::{Fatal, Transient};
 
     // Check for partial download to support resume
     let mut existing_len = if part_path.exists() {
-        std::fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0)
+        std::fs::metadata(part_path).map(|m| m.len()).unwrap_or(0)
     } else {
         0
     };
 
-    let mut request = client.get(&url);
+    let mut request = client.get(url);
     if existing_len > 0 {
         tracing::info!("resuming model download from {existing_len} bytes");
-        println!("Resuming from {} bytes...", existing_len);
+        pb.suspend(|| eprintln!("Resuming from {} bytes...", existing_len));
         request = request.header("Range", format!("bytes={}-", existing_len));
     }
 
-    let response = request
-        .send()
-        .await
-        .map_err(|e| WhsprError::Download(format!("failed to start download: {e}")))?;
+    let response = request.send().await.map_err(|e| {
+        Transient(WhsprError::Download(format!(
+            "failed to start download: {e}"
+        )))
+    })?;
 
     let original_len = existing_len;
-    existing_len = validated_existing_len(existing_len, response.status())?;
+    let status = response.status();
+    existing_len = validated_existing_len(existing_len, status).map_err(|e| {
+        if status.is_server_error() {
+            Transient(e)
+        } else {
+            Fatal(e)
+        }
+    })?;
     if original_len > 0 && existing_len == 0 {
         tracing::warn!("server ignored range request, restarting model download from zero");
-        println!("Server ignored range request, restarting download from zero");
+        pb.suspend(|| eprintln!("Server ignored range request, restarting download from zero"));
     }
 
     let total_size = if existing_len > 0 {
@@ -249,14 +298,11 @@ pub(crate) async fn download_model_from_base(name: &str, base_url: &str) -> Resu
     } else {
         response.content_length().unwrap_or(0)
     };
-
-    let pb = ProgressBar::new(total_size);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("{spinner:.green} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
-            .unwrap()
-            .progress_chars("#>-"),
-    );
+    // Content-length is what is left to fetch, for both full and range requests.
+    if let (Some(remaining), Some(dir)) = (response.content_length(), part_path.parent()) {
+        check_disk_space(dir, remaining).map_err(Fatal)?;
+    }
+    pb.set_length(total_size);
     pb.set_position(existing_len);
 
     let mut open_opts = tokio::fs::OpenOptions::new();
@@ -267,33 +313,200 @@ pub(crate) async fn download_model_from_base(name: &str, base_url: &str) -> Resu
         open_opts.write(true).truncate(true);
     }
     let mut file = open_opts
-        .open(&part_path)
+        .open(part_path)
         .await
-        .map_err(|e| WhsprError::Download(format!("failed to open file: {e}")))?;
+        .map_err(|e| Fatal(WhsprError::Download(format!("failed to open file: {e}"))))?;
 
     let mut stream = response.bytes_stream();
+    let mut interrupted = None;
     while let Some(chunk) = stream.next().await {
-        let chunk =
-            chunk.map_err(|e| WhsprError::Download(format!("download interrupted: {e}")))?;
-        file.write_all(&chunk)
-            .await
-            .map_err(|e| WhsprError::Download(format!("failed to write: {e}")))?;
-        pb.inc(chunk.len() as u64);
+        match chunk {
+            Ok(chunk) => {
+                file.write_all(&chunk)
+                    .await
+                    .map_err(|e| Fatal(WhsprError::Download(format!("failed to write: {e}"))))?;
+                pb.inc(chunk.len() as u64);
+            }
+            Err(e) => {
+                interrupted = Some(e);
+                break;
+            }
+        }
     }
 
+    // Flush even after an interruption, so a retry resumes from what is on disk.
     file.flush()
         .await
-        .map_err(|e| WhsprError::Download(format!("failed to flush: {e}")))?;
-    drop(file);
+        .map_err(|e| Fatal(WhsprError::Download(format!("failed to flush: {e}"))))?;
+    match interrupted {
+        Some(e) => Err(Transient(WhsprError::Download(format!(
+            "download interrupted: {e}"
+        )))),
+        None => Ok(()),
+    }
+}
+
+/// Free space left over after a download, so a model never fills the disk
+/// to the last byte.
+const DISK_SPACE_MARGIN: u64 = 100 * 1024 * 1024;
+
+/// Bytes available to unprivileged users on the filesystem holding `dir`.
+fn available_space(dir: &Path) -> std::io::Result<u64> {
+    let path = CString::new(dir.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Fail before downloading `needed` more bytes into `dir` if they won't fit.
+/// When the free space can't be read, the download goes ahead.
+fn check_disk_space(dir: &Path, needed: u64) -> Result<()> {
+    let available = match available_space(dir) {
+        Ok(available) => available,
+        Err(e) => {
+            tracing::warn!("failed to check free space in {}: {e}", dir.display());
+            return Ok(());
+        }
+    };
+    if needed.saturating_add(DISK_SPACE_MARGIN) > available {
+        return Err(WhsprError::Download(format!(
+            "not enough disk space in {}: the download needs {} but only {} is free. \
+             Free up some space and run the download again to resume it",
+            dir.display(),
+            format_size(needed),
+            format_size(available)
+        )));
+    }
+    Ok(())
+}
+
+/// Build a download URL from a template. `{filename}` is substituted; a
+/// template without the placeholder is treated as a base URL.
+fn model_url(template: &str, filename: &str) -> String {
+    if template.contains("{filename}") {
+        template.replace("{filename}", filename)
+    } else {
+        format!("{}/{}", template.trim_end_matches('/'), filename)
+    }
+}
+
+/// How many times a download that drops is resumed before giving up, unless
+/// `model download --retries` says otherwise.
+pub const DEFAULT_DOWNLOAD_RETRIES: u32 = 3;
+
+pub async fn download_model(
+    name: &str,
+    config_path_override: Option<&Path>,
+    verify: bool,
+    retries: u32,
+) -> Result<PathBuf> {
+    let config = config::Config::load(config_path_override)?;
+    download_model_from_base(name, &config.model.base_url, verify, retries).await
+}
+
+/// Path of a built-in model for one-off use, downloading it first if needed.
+/// The config is left alone.
+pub async fn ensure_model(name: &str, base_url: &str, verify: bool) -> Result<PathBuf> {
+    if let Some(info) = find_model(name) {
+        let path = model_path(info.filename);
+        if path.exists() {
+            return Ok(path);
+        }
+    }
+    download_model_from_base(name, base_url, verify, DEFAULT_DOWNLOAD_RETRIES).await
+}
+
+/// Download a built-in model from `base_url`. Status lines go to stderr, like
+/// the progress bar, so `transcribe --model` keeps stdout for the transcript.
+pub(crate) async fn download_model_from_base(
+    name: &str,
+    base_url: &str,
+    verify: bool,
+    retries: u32,
+) -> Result<PathBuf> {
+    let info = find_model(name).ok_or_else(|| {
+        let available: Vec<&str> = MODELS.iter().map(|m| m.name).collect();
+        WhsprError::Download(format!(
+            "unknown model '{}'. Available: {}",
+            name,
+            available.join(", ")
+        ))
+    })?;
+
+    let dest = model_path(info.filename);
+    let part_path = dest.with_extension("bin.part");
+
+    if dest.exists() {
+        tracing::info!("model '{name}' already downloaded at {}", dest.display());
+        eprintln!("Model '{}' already downloaded at {}", name, dest.display());
+        return Ok(dest);
+    }
+
+    // Ensure data directory exists
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| WhsprError::Download(format!("failed to create data directory: {e}")))?;
+    }
+
+    let url = model_url(base_url, info.filename);
+    tracing::info!("downloading model '{}' from {}", info.name, url);
+
+    eprintln!("Downloading {} ({})...", info.name, info.size);
+
+    let client = reqwest::Client::new();
+    let pb = ProgressBar::new(0);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+
+    let mut attempt = 0;
+    loop {
+        match fetch_to_part(&client, &url, &part_path, &pb).await {
+            Ok(()) => break,
+            Err(FetchError::Transient(e)) if attempt < retries => {
+                attempt += 1;
+                let delay = retry_delay(attempt);
+                tracing::warn!(
+                    "{e}, retrying in {}s ({attempt}/{retries})",
+                    delay.as_secs()
+                );
+                pb.suspend(|| {
+                    eprintln!(
+                        "{e}, retrying in {}s ({attempt}/{retries})",
+                        delay.as_secs()
+                    )
+                });
+                tokio::time::sleep(delay).await;
+            }
+            Err(FetchError::Transient(e) | FetchError::Fatal(e)) => return Err(e),
+        }
+    }
 
     pb.finish_with_message("done");
 
+    if verify {
+        eprintln!("Verifying checksum...");
+        let path = part_path.clone();
+        let expected = info.sha256;
+        tokio::task::spawn_blocking(move || verify_checksum(&path, expected))
+            .await
+            .map_err(|e| WhsprError::Download(format!("checksum task failed: {e}")))??;
+        tracing::info!("checksum verified for model '{}'", info.name);
+    } else {
+        tracing::warn!("checksum verification disabled for model '{}'", info.name);
+    }
+
     // Atomic rename
     std::fs::rename(&part_path, &dest)
         .map_err(|e| WhsprError::Download(format!("failed to finalize download: {e}")))?;
 
     tracing::info!("model '{}' saved to {}", info.name, dest.display());
-    println!("Saved to {}", dest.display());
+    eprintln!("Saved to {}", dest.display());
     Ok(dest)
 }
 
@@ -309,36 +522,145 @@ pub fn select_model(name: &str, config_path_override: Option<&Path>) -> Result<(
         )));
     }
 
-    let config_path = resolve_config_path(config_path_override);
-    let model_path_str = model_path_for_config(info.filename);
+    write_model_selection(&model_path_for_config(info.filename), config_path_override)?;
+    println!("Selected model '{}' as active.", name);
+    println!(
+        "Config updated: {}",
+        resolve_config_path(config_path_override).display()
+    );
+    Ok(())
+}
+
+/// Select a model file outside the built-in list, e.g. a fine-tuned one.
+pub fn select_model_path(path: &Path, config_path_override: Option<&Path>) -> Result<()> {
+    let path = std::fs::canonicalize(path)
+        .map_err(|e| WhsprError::Download(format!("cannot use {}: {e}", path.display())))?;
+    if !path.is_file() {
+        return Err(WhsprError::Download(format!(
+            "{} is not a file",
+            path.display()
+        )));
+    }
+    whspr_rs::transcribe::check_ggml_magic(&path)?;
 
+    let home = std::env::var("HOME").ok().map(PathBuf::from);
+    write_model_selection(
+        &path_for_config(&path, home.as_deref()),
+        config_path_override,
+    )?;
+    println!("Selected {} as active.", path.display());
+    println!(
+        "Config updated: {}",
+        resolve_config_path(config_path_override).display()
+    );
+    Ok(())
+}
+
+fn write_model_selection(model_path_str: &str, config_path_override: Option<&Path>) -> Result<()> {
+    let config_path = resolve_config_path(config_path_override);
     if config_path.exists() {
         tracing::info!(
             "updating model selection in config {} to {}",
             config_path.display(),
             model_path_str
         );
-        update_config_model_path(&config_path, &model_path_str)?;
+        update_config_model_path(&config_path, model_path_str)
     } else {
         tracing::info!(
             "writing new config {} with selected model {}",
             config_path.display(),
             model_path_str
         );
-        config::write_default_config(&config_path, &model_path_str)?;
+        config::write_default_config(&config_path, model_path_str)
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    const GB: f64 = MB * 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= GB {
+        format!("{:.1} GB", bytes / GB)
+    } else {
+        format!("{:.0} MB", bytes / MB)
     }
+}
 
-    println!("Selected model '{}' as active.", name);
-    println!("Config updated: {}", config_path.display());
+pub fn model_info(name: &str, config_path_override: Option<&Path>) -> Result<()> {
+    let info =
+        find_model(name).ok_or_else(|| WhsprError::Download(format!("unknown model '{name}'")))?;
+    let config = config::Config::load(config_path_override)?;
+    let active_resolved =
+        active_model_path(config_path_override).map(|p| PathBuf::from(config::expand_tilde(&p)));
+    print!(
+        "{}",
+        describe_model(info, &config.model.base_url, active_resolved.as_deref())
+    );
+    Ok(())
+}
+
+/// The `model info` report, one `key: value` line per field.
+fn describe_model(info: &ModelInfo, base_url: &str, active_resolved: Option<&Path>) -> String {
+    let on_disk = std::fs::metadata(model_path(info.filename))
+        .map(|m| format_size(m.len()))
+        .unwrap_or_else(|_| "-".into());
+    let fields = [
+        ("name", info.name.to_string()),
+        ("description", info.description.to_string()),
+        ("filename", info.filename.to_string()),
+        ("size", info.size.to_string()),
+        ("sha256", info.sha256.to_string()),
+        ("url", model_url(base_url, info.filename)),
+        ("path", model_path_for_config(info.filename)),
+        ("status", model_status(info, active_resolved).to_string()),
+        ("on disk", on_disk),
+    ];
+    fields
+        .iter()
+        .map(|(key, value)| format!("{:<12} {}\n", format!("{key}:"), value))
+        .collect()
+}
+
+pub fn delete_model(name: &str, config_path_override: Option<&Path>) -> Result<()> {
+    let info =
+        find_model(name).ok_or_else(|| WhsprError::Download(format!("unknown model '{name}'")))?;
+
+    let dest = model_path(info.filename);
+    let part_path = dest.with_extension("bin.part");
+    if part_path.exists() {
+        tracing::info!("removing partial download {}", part_path.display());
+        let _ = std::fs::remove_file(&part_path);
+    }
+
+    if !dest.exists() {
+        println!("Model '{}' is not downloaded, nothing to delete.", name);
+        return Ok(());
+    }
+
+    let active_resolved =
+        active_model_path(config_path_override).map(|p| PathBuf::from(config::expand_tilde(&p)));
+    if model_status(info, active_resolved.as_deref()) == "active" {
+        return Err(WhsprError::Download(format!(
+            "model '{}' is the active model. Select another model first with: whspr-rs model select <name>",
+            name
+        )));
+    }
+
+    let freed = std::fs::metadata(&dest).map(|m| m.len()).unwrap_or(0);
+    std::fs::remove_file(&dest)
+        .map_err(|e| WhsprError::Download(format!("failed to delete {}: {e}", dest.display())))?;
+
+    tracing::info!("deleted model '{}' at {}", name, dest.display());
+    println!("Deleted model '{}' ({} freed).", name, format_size(freed));
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::Config;
-    use crate::error::WhsprError;
     use httpmock::prelude::*;
+    use whspr_rs::config::{Config, DEFAULT_MODEL_URL};
+    use whspr_rs::error::WhsprError;
 
     #[test]
     fn path_for_config_uses_tilde_when_under_home() {
@@ -378,10 +700,36 @@ mod tests {
         assert!(err.is_err());
     }
 
+    #[test]
+    fn verify_checksum_accepts_matching_hash() {
+        let path = crate::test_support::unique_temp_path("checksum-ok", "bin");
+        std::fs::write(&path, b"abc").expect("write file");
+        verify_checksum(
+            &path,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        )
+        .expect("checksum should match");
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn verify_checksum_removes_file_on_mismatch() {
+        let path = crate::test_support::unique_temp_path("checksum-bad", "bin");
+        std::fs::write(&path, b"abc").expect("write file");
+        let err = verify_checksum(&path, &"0".repeat(64)).expect_err("checksum should mismatch");
+        match err {
+            WhsprError::Download(msg) => {
+                assert!(msg.contains("checksum mismatch"), "unexpected: {msg}");
+            }
+            other => panic!("unexpected error variant: {other:?}"),
+        }
+        assert!(!path.exists(), "corrupt download should be removed");
+    }
+
     #[test]
     fn active_model_path_uses_override_config() {
         let config_path = crate::test_support::unique_temp_path("active-model-config", "toml");
-        crate::config::write_default_config(&config_path, "~/override-model.bin")
+        whspr_rs::config::write_default_config(&config_path, "~/override-model.bin")
             .expect("write config");
         let active = active_model_path(Some(&config_path));
         assert_eq!(active.as_deref(), Some("~/override-model.bin"));
@@ -459,6 +807,157 @@ mod tests {
         );
     }
 
+    #[test]
+    fn select_model_path_accepts_only_ggml_files() {
+        let _env_lock = crate::test_support::env_lock();
+        let _guard = crate::test_support::EnvVarGuard::capture(&["HOME", "XDG_DATA_HOME"]);
+        let home = crate::test_support::unique_temp_dir("select-path-home");
+        crate::test_support::set_env("HOME", &home.to_string_lossy());
+        crate::test_support::remove_env("XDG_DATA_HOME");
+
+        let config_path = crate::test_support::unique_temp_path("select-path-config", "toml");
+        let model_file = home.join("ggml-finetuned.bin");
+        std::fs::write(&model_file, b"<html>not a model</html>").expect("write model");
+        assert!(select_model_path(&model_file, Some(&config_path)).is_err());
+        assert!(!config_path.exists());
+
+        std::fs::write(&model_file, [&b"lmgg"[..], &[0u8; 8]].concat()).expect("write model");
+        select_model_path(&model_file, Some(&config_path)).expect("select model path");
+        let loaded = Config::load(Some(&config_path)).expect("load selected config");
+        assert_eq!(loaded.whisper.model_path, "~/ggml-finetuned.bin");
+        assert!(is_custom_model(&model_file));
+        assert!(!is_custom_model(&model_path("ggml-tiny.bin")));
+    }
+
+    #[test]
+    fn model_url_substitutes_filename_placeholder() {
+        assert_eq!(
+            model_url(
+                "https://mirror.example/whisper/{filename}?raw=1",
+                "ggml-tiny.bin"
+            ),
+            "https://mirror.example/whisper/ggml-tiny.bin?raw=1"
+        );
+        assert_eq!(
+            model_url(DEFAULT_MODEL_URL, "ggml-tiny.bin"),
+            "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.bin"
+        );
+    }
+
+    #[test]
+    fn model_url_appends_filename_to_plain_base() {
+        assert_eq!(
+            model_url("http://127.0.0.1:8080/", "ggml-tiny.bin"),
+            "http://127.0.0.1:8080/ggml-tiny.bin"
+        );
+    }
+
+    #[test]
+    fn format_size_uses_mb_and_gb() {
+        assert_eq!(format_size(78 * 1024 * 1024), "78 MB");
+        assert_eq!(format_size(1717986918), "1.6 GB");
+    }
+
+    #[test]
+    fn describe_model_reports_url_path_and_status() {
+        let _env_lock = crate::test_support::env_lock();
+        let _guard = crate::test_support::EnvVarGuard::capture(&["HOME", "XDG_DATA_HOME"]);
+        let home = crate::test_support::unique_temp_dir("describe-model-home");
+        crate::test_support::set_env("HOME", &home.to_string_lossy());
+        crate::test_support::remove_env("XDG_DATA_HOME");
+
+        let tiny = find_model("tiny").expect("tiny model should exist");
+        let report = describe_model(tiny, "https://mirror.example/models", None);
+        assert!(report.contains("url:         https://mirror.example/models/ggml-tiny.bin"));
+        assert!(report.contains("path:        ~/.local/share/whspr-rs/ggml-tiny.bin"));
+        assert!(report.contains("status:      remote\n"));
+        assert!(report.contains("on disk:     -\n"));
+
+        let path = model_path(tiny.filename);
+        std::fs::create_dir_all(path.parent().expect("model parent")).expect("create model dir");
+        std::fs::write(&path, b"stub").expect("write model");
+        let report = describe_model(tiny, DEFAULT_MODEL_URL, Some(&path));
+        assert!(report.contains("status:      active\n"));
+        assert!(report.contains("on disk:     0 MB\n"));
+    }
+
+    #[test]
+    fn delete_model_refuses_active_model() {
+        let _env_lock = crate::test_support::env_lock();
+        let _guard = crate::test_support::EnvVarGuard::capture(&["HOME", "XDG_DATA_HOME"]);
+        let home = crate::test_support::unique_temp_dir("delete-active-home");
+        crate::test_support::set_env("HOME", &home.to_string_lossy());
+        crate::test_support::remove_env("XDG_DATA_HOME");
+
+        let tiny = find_model("tiny").expect("tiny model should exist");
+        let path = model_path(tiny.filename);
+        std::fs::create_dir_all(path.parent().expect("model parent")).expect("create model dir");
+        std::fs::write(&path, b"stub").expect("write model");
+
+        let config_path = crate::test_support::unique_temp_path("delete-active-config", "toml");
+        whspr_rs::config::write_default_config(&config_path, &model_path_for_config(tiny.filename))
+            .expect("write config");
+
+        let err = delete_model("tiny", Some(&config_path)).expect_err("should refuse");
+        match err {
+            WhsprError::Download(msg) => {
+                assert!(msg.contains("active model"), "unexpected: {msg}");
+            }
+            other => panic!("unexpected error variant: {other:?}"),
+        }
+        assert!(path.exists(), "active model must not be deleted");
+    }
+
+    #[test]
+    fn delete_model_removes_inactive_model_and_tolerates_missing() {
+        let _env_lock = crate::test_support::env_lock();
+        let _guard = crate::test_support::EnvVarGuard::capture(&["HOME", "XDG_DATA_HOME"]);
+        let home = crate::test_support::unique_temp_dir("delete-inactive-home");
+        crate::test_support::set_env("HOME", &home.to_string_lossy());
+        crate::test_support::remove_env("XDG_DATA_HOME");
+
+        let tiny = find_model("tiny").expect("tiny model should exist");
+        let path = model_path(tiny.filename);
+        std::fs::create_dir_all(path.parent().expect("model parent")).expect("create model dir");
+        std::fs::write(&path, b"stub").expect("write model");
+
+        let config_path = crate::test_support::unique_temp_path("delete-inactive-config", "toml");
+        delete_model("tiny", Some(&config_path)).expect("delete model");
+        assert!(!path.exists());
+
+        delete_model("tiny", Some(&config_path)).expect("missing model is not an error");
+    }
+
+    #[test]
+    fn ensure_model_downloads_only_when_missing() {
+        let _env_lock = crate::test_support::env_lock();
+        let _guard = crate::test_support::EnvVarGuard::capture(&["HOME", "XDG_DATA_HOME"]);
+        let home = crate::test_support::unique_temp_dir("ensure-model-home");
+        crate::test_support::set_env("HOME", &home.to_string_lossy());
+        crate::test_support::remove_env("XDG_DATA_HOME");
+
+        let tiny = find_model("tiny").expect("tiny model should exist");
+        let server = MockServer::start();
+        let download = server.mock(|when, then| {
+            when.method(GET).path(format!("/{}", tiny.filename));
+            then.status(200).body("model");
+        });
+
+        let runtime = tokio::runtime::Runtime::new().expect("runtime");
+        for _ in 0..2 {
+            let path = runtime
+                .block_on(ensure_model("tiny", &server.base_url(), false))
+                .expect("ensure model");
+            assert_eq!(path, model_path(tiny.filename));
+        }
+        download.assert_hits(1);
+        assert!(
+            runtime
+                .block_on(ensure_model("nope", &server.base_url(), false))
+                .is_err()
+        );
+    }
+
     #[test]
     fn download_model_from_base_resumes_partial_download() {
         let _env_lock = crate::test_support::env_lock();
@@ -483,7 +982,12 @@ mod tests {
 
         let runtime = tokio::runtime::Runtime::new().expect("runtime");
         let result = runtime
-            .block_on(download_model_from_base("tiny", &server.base_url()))
+            .block_on(download_model_from_base(
+                "tiny",
+                &server.base_url(),
+                false,
+                0,
+            ))
             .expect("download should succeed");
         resumed.assert();
         assert_eq!(result, dest);
@@ -516,7 +1020,12 @@ mod tests {
 
         let runtime = tokio::runtime::Runtime::new().expect("runtime");
         let result = runtime
-            .block_on(download_model_from_base("tiny", &server.base_url()))
+            .block_on(download_model_from_base(
+                "tiny",
+                &server.base_url(),
+                false,
+                0,
+            ))
             .expect("download should succeed");
         restarted.assert();
         assert_eq!(result, dest);
@@ -526,4 +1035,89 @@ mod tests {
         );
         assert!(!part_path.exists(), "part file should be renamed away");
     }
+
+    #[test]
+    fn download_model_from_base_rejects_checksum_mismatch() {
+        let _env_lock = crate::test_support::env_lock();
+        let _guard = crate::test_support::EnvVarGuard::capture(&["HOME", "XDG_DATA_HOME"]);
+        let home = crate::test_support::unique_temp_dir("download-mismatch-home");
+        crate::test_support::set_env("HOME", &home.to_string_lossy());
+        crate::test_support::remove_env("XDG_DATA_HOME");
+
+        let tiny = find_model("tiny").expect("tiny model should exist");
+        let dest = model_path(tiny.filename);
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path(format!("/{}", tiny.filename));
+            then.status(200).header("content-length", "3").body("bad");
+        });
+
+        let runtime = tokio::runtime::Runtime::new().expect("runtime");
+        let err = runtime
+            .block_on(download_model_from_base(
+                "tiny",
+                &server.base_url(),
+                true,
+                0,
+            ))
+            .expect_err("a corrupted download should be rejected");
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+        assert!(!dest.exists(), "a corrupted model must not be installed");
+        assert!(!dest.with_extension("bin.part").exists());
+    }
+
+    #[test]
+    fn every_model_has_a_sha256() {
+        for model in MODELS {
+            assert_eq!(model.sha256.len(), 64, "{}", model.name);
+            assert!(
+                model.sha256.chars().all(|c| c.is_ascii_hexdigit()),
+                "{}",
+                model.name
+            );
+        }
+    }
+
+    #[test]
+    fn check_disk_space_rejects_downloads_that_would_not_fit() {
+        let dir = std::env::temp_dir();
+        assert!(check_disk_space(&dir, 0).is_ok());
+        let err = check_disk_space(&dir, u64::MAX / 2).expect_err("an exabyte should not fit");
+        assert!(err.to_string().contains("not enough disk space"), "{err}");
+    }
+
+    #[test]
+    fn retry_delay_backs_off_exponentially() {
+        let secs: Vec<u64> = (1..=7).map(|n| retry_delay(n).as_secs()).collect();
+        assert_eq!(secs, [1, 2, 4, 8, 16, 32, 32]);
+    }
+
+    #[test]
+    fn download_model_from_base_retries_server_errors_only() {
+        let _env_lock = crate::test_support::env_lock();
+        let _guard = crate::test_support::EnvVarGuard::capture(&["HOME", "XDG_DATA_HOME"]);
+        let home = crate::test_support::unique_temp_dir("download-retry-home");
+        crate::test_support::set_env("HOME", &home.to_string_lossy());
+        crate::test_support::remove_env("XDG_DATA_HOME");
+
+        let tiny = find_model("tiny").expect("tiny model should exist");
+        let runtime = tokio::runtime::Runtime::new().expect("runtime");
+        for (status, hits) in [(404, 1), (503, 2)] {
+            let server = MockServer::start();
+            let failing = server.mock(|when, then| {
+                when.method(GET).path(format!("/{}", tiny.filename));
+                then.status(status);
+            });
+            let err = runtime
+                .block_on(download_model_from_base(
+                    "tiny",
+                    &server.base_url(),
+                    true,
+                    1,
+                ))
+                .expect_err("download should fail");
+            assert!(err.to_string().contains(&status.to_string()), "{err}");
+            failing.assert_hits(hits);
+        }
+    }
 }
@@ -0,0 +1,171 @@
+//! `[osd]` settings, shared with the whspr-osd binary.
+//!
+//! whspr-rs loads them as part of `Config` and hands them to the overlay in
+//! the `WHSPR_OSD_CONFIG` environment variable as TOML. A whspr-osd started
+//! some other way reads `[osd]` from the config file itself.
+
+use serde::{Deserialize, Serialize};
+
+/// Environment variable whspr-rs uses to pass `[osd]` to whspr-osd.
+pub const OSD_CONFIG_ENV: &str = "WHSPR_OSD_CONFIG";
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct OsdConfig {
+    /// Launch whspr-osd while recording. Turn off if you run it yourself.
+    pub autostart: bool,
+    pub background: Color,
+    pub border: Color,
+    /// Visualizer gradient, left to right.
+    pub bar_left: Color,
+    pub bar_right: Color,
+    /// Color of the transcript shown after dictation.
+    pub text: Color,
+    /// Screen edge the overlay is attached to.
+    pub anchor: Anchor,
+    /// Distance from the anchored edge in pixels.
+    pub margin: i32,
+    /// How the recording overlay visualizes the microphone.
+    pub style: OsdStyle,
+    /// `wl_output` name (connector, e.g. "DP-1") to show the overlay on.
+    /// Empty leaves the choice to the compositor.
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OsdStyle {
+    /// Animated bars driven by the input level.
+    #[default]
+    Bars,
+    /// The most recent samples plotted as a waveform.
+    Waveform,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Anchor {
+    Top,
+    #[default]
+    Bottom,
+    Left,
+    Right,
+}
+
+/// An RGBA color written as `"#rrggbb"` or `"#rrggbbaa"` in config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("invalid color '{value}', expected #rrggbb or #rrggbbaa");
+        let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+        if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+            return Err(invalid());
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+        Ok(Self {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+            a: if hex.len() == 8 { channel(6)? } else { 255 },
+        })
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            color.r, color.g, color.b, color.a
+        )
+    }
+}
+
+impl Default for OsdConfig {
+    fn default() -> Self {
+        Self {
+            autostart: true,
+            background: Color::rgba(18, 18, 30, 185),
+            border: Color::rgba(140, 180, 255, 40),
+            bar_left: Color::rgba(0, 209, 191, 255),
+            bar_right: Color::rgba(166, 89, 255, 255),
+            text: Color::rgba(235, 238, 250, 255),
+            anchor: Anchor::default(),
+            margin: 40,
+            style: OsdStyle::default(),
+            output: String::new(),
+        }
+    }
+}
+
+/// `[osd]` from the contents of a whspr-rs config file; the other sections
+/// are ignored and a file without `[osd]` gives the defaults.
+pub fn from_config_file(contents: &str) -> Result<OsdConfig, toml::de::Error> {
+    #[derive(Default, Deserialize)]
+    #[serde(default)]
+    struct ConfigFile {
+        osd: OsdConfig,
+    }
+    toml::from_str::<ConfigFile>(contents).map(|file| file.osd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_parses_rgb_and_rgba() {
+        assert_eq!(
+            Color::try_from("#ff8000".to_string()),
+            Ok(Color::rgba(255, 128, 0, 255))
+        );
+        assert_eq!(
+            Color::try_from("#12121eb9".to_string()),
+            Ok(Color::rgba(18, 18, 30, 185))
+        );
+        assert!(Color::try_from("ff8000".to_string()).is_err());
+        assert!(Color::try_from("#ff80".to_string()).is_err());
+        assert!(Color::try_from("#gg8000".to_string()).is_err());
+    }
+
+    #[test]
+    fn osd_config_roundtrips_through_toml() {
+        let config = OsdConfig {
+            anchor: Anchor::Top,
+            margin: 12,
+            style: OsdStyle::Waveform,
+            output: "DP-1".to_string(),
+            ..OsdConfig::default()
+        };
+        let encoded = toml::to_string(&config).expect("serialize osd config");
+        let decoded: OsdConfig = toml::from_str(&encoded).expect("parse osd config");
+        assert_eq!(decoded, config);
+    }
+
+    #[test]
+    fn from_config_file_reads_only_the_osd_section() {
+        let contents = "[audio]\ndevice = \"USB\"\n\n[osd]\nanchor = \"top\"\nmargin = 12\n";
+        let config = from_config_file(contents).expect("parse config file");
+        assert_eq!(config.anchor, Anchor::Top);
+        assert_eq!(config.margin, 12);
+        assert_eq!(config.style, OsdStyle::Bars);
+
+        let config = from_config_file("[audio]\ndevice = \"USB\"\n").expect("parse config file");
+        assert_eq!(config, OsdConfig::default());
+    }
+}
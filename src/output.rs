@@ -0,0 +1,224 @@
+use clap::ValueEnum;
+
+use whspr_rs::transcribe::{Segment, Transcript, segments_to_text};
+
+/// Output formats supported by `whspr-rs transcribe`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Plain text transcript
+    #[default]
+    Txt,
+    /// SubRip subtitles
+    Srt,
+    /// WebVTT subtitles
+    Vtt,
+    /// JSON with language and per-segment metadata (and per-word timing
+    /// when `word_timestamps` is enabled)
+    Json,
+}
+
+impl OutputFormat {
+    /// Conventional file extension for the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Txt => "txt",
+            OutputFormat::Srt => "srt",
+            OutputFormat::Vtt => "vtt",
+            OutputFormat::Json => "json",
+        }
+    }
+}
+
+/// One line per format: its `--format` name and what it produces.
+pub fn format_list() -> String {
+    OutputFormat::value_variants()
+        .iter()
+        .filter_map(|format| format.to_possible_value())
+        .map(|value| {
+            let help = value.get_help().map(|h| h.to_string()).unwrap_or_default();
+            format!("{:<6}{help}\n", value.get_name())
+        })
+        .collect()
+}
+
+/// Expand an output file name template: `{stem}` is the input file name
+/// without its extension, `{ext}` the format's extension and `{lang}` the
+/// transcript language.
+pub fn file_name(template: &str, stem: &str, format: OutputFormat, language: &str) -> String {
+    template
+        .replace("{stem}", stem)
+        .replace("{ext}", format.extension())
+        .replace("{lang}", language)
+}
+
+pub fn render(format: OutputFormat, transcript: &Transcript) -> String {
+    match format {
+        OutputFormat::Txt => segments_to_text(&transcript.segments),
+        OutputFormat::Srt => render_srt(&transcript.segments),
+        OutputFormat::Vtt => render_vtt(&transcript.segments),
+        OutputFormat::Json => render_json(transcript),
+    }
+}
+
+fn render_srt(segments: &[Segment]) -> String {
+    let mut out = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n",
+            i + 1,
+            format_timestamp(segment.start, ','),
+            format_timestamp(segment.end, ','),
+            segment.text.trim()
+        ));
+    }
+    out
+}
+
+fn render_vtt(segments: &[Segment]) -> String {
+    let mut out = String::from("WEBVTT\n");
+    for segment in segments {
+        out.push_str(&format!(
+            "\n{} --> {}\n{}\n",
+            format_timestamp(segment.start, '.'),
+            format_timestamp(segment.end, '.'),
+            segment.text.trim()
+        ));
+    }
+    out
+}
+
+fn render_json(transcript: &Transcript) -> String {
+    let mut transcript = transcript.clone();
+    for segment in &mut transcript.segments {
+        segment.text = segment.text.trim().to_string();
+    }
+    serde_json::to_string_pretty(&transcript).expect("transcript serializes to JSON")
+}
+
+/// Format seconds as `HH:MM:SS<sep>mmm`.
+fn format_timestamp(secs: f64, millis_sep: char) -> String {
+    let total_ms = (secs.max(0.0) * 1000.0).round() as u64;
+    let hours = total_ms / 3_600_000;
+    let minutes = (total_ms / 60_000) % 60;
+    let seconds = (total_ms / 1000) % 60;
+    let millis = total_ms % 1000;
+    format!("{hours:02}:{minutes:02}:{seconds:02}{millis_sep}{millis:03}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use whspr_rs::transcribe::Word;
+
+    #[test]
+    fn format_list_names_every_format() {
+        let list = format_list();
+        assert_eq!(list.lines().count(), OutputFormat::value_variants().len());
+        assert!(list.starts_with("txt   Plain text transcript\n"), "{list}");
+        assert!(list.contains("srt   SubRip subtitles"), "{list}");
+    }
+
+    fn segment(text: &str, start: f64, end: f64) -> Segment {
+        Segment {
+            text: text.to_string(),
+            start,
+            end,
+            no_speech_prob: 0.0,
+            words: Vec::new(),
+        }
+    }
+
+    fn transcript(segments: Vec<Segment>) -> Transcript {
+        Transcript {
+            language: "en".into(),
+            segments,
+        }
+    }
+
+    #[test]
+    fn file_name_expands_placeholders() {
+        assert_eq!(
+            file_name("{stem}.{ext}", "talk", OutputFormat::Srt, "en"),
+            "talk.srt"
+        );
+        assert_eq!(
+            file_name("{stem}.{lang}.{ext}", "talk", OutputFormat::Vtt, "fr"),
+            "talk.fr.vtt"
+        );
+        assert_eq!(
+            file_name("out.txt", "talk", OutputFormat::Json, "en"),
+            "out.txt"
+        );
+    }
+
+    #[test]
+    fn format_timestamp_handles_hours_and_millis() {
+        assert_eq!(format_timestamp(0.0, ','), "00:00:00,000");
+        assert_eq!(format_timestamp(61.5, ','), "00:01:01,500");
+        assert_eq!(format_timestamp(3723.042, ','), "01:02:03,042");
+    }
+
+    #[test]
+    fn render_srt_numbers_cues_and_trims_text() {
+        let segments = vec![
+            segment(" Hello there.", 0.0, 1.25),
+            segment(" General Kenobi.", 31.0, 32.5),
+        ];
+        assert_eq!(
+            render(OutputFormat::Srt, &transcript(segments)),
+            "1\n00:00:00,000 --> 00:00:01,250\nHello there.\n\n\
+             2\n00:00:31,000 --> 00:00:32,500\nGeneral Kenobi.\n"
+        );
+    }
+
+    #[test]
+    fn render_vtt_emits_header_and_dot_timestamps() {
+        let segments = vec![segment(" Hello there.", 1.5, 2.0)];
+        assert_eq!(
+            render(OutputFormat::Vtt, &transcript(segments)),
+            "WEBVTT\n\n00:00:01.500 --> 00:00:02.000\nHello there.\n"
+        );
+    }
+
+    #[test]
+    fn render_txt_joins_segment_text() {
+        let segments = vec![segment(" Hello", 0.0, 1.0), segment(" world.", 1.0, 2.0)];
+        assert_eq!(
+            render(OutputFormat::Txt, &transcript(segments)),
+            "Hello world."
+        );
+    }
+
+    #[test]
+    fn render_json_includes_language_and_segment_metadata() {
+        let rendered = render(
+            OutputFormat::Json,
+            &transcript(vec![segment(" Hello.", 0.5, 1.0)]),
+        );
+        let value: serde_json::Value = serde_json::from_str(&rendered).expect("valid json");
+        assert_eq!(value["language"], "en");
+        assert_eq!(value["segments"][0]["text"], "Hello.");
+        assert_eq!(value["segments"][0]["start"], 0.5);
+        assert_eq!(value["segments"][0]["end"], 1.0);
+        assert_eq!(value["segments"][0]["no_speech_prob"], 0.0);
+        assert!(value["segments"][0].get("words").is_none());
+    }
+
+    #[test]
+    fn render_json_includes_words_when_present() {
+        let mut with_words = segment(" Hi.", 0.0, 0.5);
+        with_words.words = vec![Word {
+            text: "Hi.".into(),
+            start: 0.0,
+            end: 0.5,
+            probability: 0.9,
+        }];
+        let rendered = render(OutputFormat::Json, &transcript(vec![with_words]));
+        let value: serde_json::Value = serde_json::from_str(&rendered).expect("valid json");
+        assert_eq!(value["segments"][0]["words"][0]["text"], "Hi.");
+        assert_eq!(value["segments"][0]["words"][0]["end"], 0.5);
+    }
+}
@@ -0,0 +1,125 @@
+use whspr_rs::config::PostprocessConfig;
+
+/// Apply the configured cleanups to a transcript before it is injected.
+pub fn apply(text: &str, config: &PostprocessConfig) -> String {
+    let mut out = if config.capitalize_first {
+        capitalize_first(text)
+    } else {
+        text.to_string()
+    };
+    if config.trailing_space && !out.ends_with(char::is_whitespace) {
+        out.push(' ');
+    }
+    out
+}
+
+/// Whether the whole transcript is one of the blocklisted phrases.
+pub fn is_blocked(text: &str, config: &PostprocessConfig) -> bool {
+    let text = normalize(text);
+    !text.is_empty()
+        && config
+            .blocklist
+            .iter()
+            .any(|phrase| normalize(phrase) == text)
+}
+
+/// Lowercase, drop punctuation and collapse whitespace, so "Thank you." and
+/// " thank you" compare equal.
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Uppercase the first non-whitespace character, keeping any leading
+/// whitespace. Some characters expand when uppercased (e.g. 'ß' -> "SS").
+fn capitalize_first(text: &str) -> String {
+    let Some((idx, first)) = text.char_indices().find(|(_, c)| !c.is_whitespace()) else {
+        return text.to_string();
+    };
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push_str(&text[..idx]);
+    out.extend(first.to_uppercase());
+    out.push_str(&text[idx + first.len_utf8()..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(capitalize_first: bool, trailing_space: bool) -> PostprocessConfig {
+        PostprocessConfig {
+            capitalize_first,
+            trailing_space,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn apply_is_a_no_op_by_default() {
+        assert_eq!(
+            apply("hello world", &PostprocessConfig::default()),
+            "hello world"
+        );
+    }
+
+    #[test]
+    fn capitalize_first_skips_leading_whitespace() {
+        assert_eq!(apply("  hello", &config(true, false)), "  Hello");
+        assert_eq!(apply("   ", &config(true, false)), "   ");
+    }
+
+    #[test]
+    fn capitalize_first_handles_unicode() {
+        assert_eq!(apply("élan vital", &config(true, false)), "Élan vital");
+        assert_eq!(apply("ßtraße", &config(true, false)), "SStraße");
+        assert_eq!(apply("日本語", &config(true, false)), "日本語");
+    }
+
+    #[test]
+    fn trailing_space_is_added_once() {
+        assert_eq!(apply("done.", &config(false, true)), "done. ");
+        assert_eq!(apply("done. ", &config(false, true)), "done. ");
+    }
+
+    #[test]
+    fn is_blocked_matches_whole_transcript_ignoring_case_and_punctuation() {
+        let config = PostprocessConfig {
+            blocklist: vec![
+                "Thank you.".into(),
+                "Thanks for watching!".into(),
+                "you".into(),
+            ],
+            ..Default::default()
+        };
+        assert!(is_blocked(" Thank you.", &config));
+        assert!(is_blocked("THANKS FOR WATCHING", &config));
+        assert!(is_blocked("You", &config));
+        assert!(!is_blocked("Thank you for the review.", &config));
+        assert!(!is_blocked("", &config));
+    }
+
+    #[test]
+    fn is_blocked_is_off_by_default() {
+        let config = PostprocessConfig::default();
+        assert!(!is_blocked("Thank you.", &config));
+        assert!(!is_blocked("you", &config));
+    }
+
+    #[test]
+    fn is_blocked_uses_configured_list_only() {
+        let mut config = PostprocessConfig {
+            blocklist: vec!["Bye bye!".into()],
+            ..Default::default()
+        };
+        assert!(is_blocked("bye, bye", &config));
+        assert!(!is_blocked("Thank you.", &config));
+        config.blocklist.clear();
+        assert!(!is_blocked("bye bye", &config));
+    }
+}
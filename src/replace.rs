@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+use regex::Regex;
+
+use whspr_rs::config::Replacement;
+use whspr_rs::error::{Result, WhsprError};
+
+/// A compiled `[replacements]` entry.
+#[derive(Debug)]
+pub enum Rule {
+    Literal { pattern: String, with: String },
+    Regex { pattern: Regex, with: String },
+}
+
+/// Compile the configured replacements, failing on the first invalid regex so
+/// a typo surfaces at startup instead of silently never matching. Rules come
+/// out in alphabetical order of their keys, whatever the file order was.
+pub fn compile_rules(replacements: &BTreeMap<String, Replacement>) -> Result<Vec<Rule>> {
+    replacements
+        .iter()
+        .filter(|(pattern, _)| !pattern.is_empty())
+        .map(|(pattern, replacement)| match replacement {
+            Replacement::Literal(with) | Replacement::Rule { with, regex: false } => {
+                Ok(Rule::Literal {
+                    pattern: pattern.clone(),
+                    with: with.clone(),
+                })
+            }
+            Replacement::Rule { with, regex: true } => {
+                let compiled = Regex::new(pattern).map_err(|e| {
+                    WhsprError::Config(format!("invalid replacement regex '{pattern}': {e}"))
+                })?;
+                Ok(Rule::Regex {
+                    pattern: compiled,
+                    with: with.clone(),
+                })
+            }
+        })
+        .collect()
+}
+
+/// Apply each rule in turn to the transcript. Regex replacements may refer to
+/// capture groups with `$1` or `${name}`.
+pub fn apply_replacements(text: &str, rules: &[Rule]) -> String {
+    let mut out = text.to_string();
+    for rule in rules {
+        out = match rule {
+            Rule::Literal { pattern, with } => out.replace(pattern.as_str(), with),
+            Rule::Regex { pattern, with } => pattern.replace_all(&out, with.as_str()).into_owned(),
+        };
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(entries: &[(&str, Replacement)]) -> Vec<Rule> {
+        let map = entries
+            .iter()
+            .map(|(pattern, replacement)| ((*pattern).to_string(), replacement.clone()))
+            .collect();
+        compile_rules(&map).expect("rules should compile")
+    }
+
+    #[test]
+    fn literal_rules_replace_every_occurrence() {
+        let rules = rules(&[
+            ("get hub", Replacement::Literal("GitHub".into())),
+            ("pie torch", Replacement::Literal("PyTorch".into())),
+        ]);
+        assert_eq!(
+            apply_replacements("push to get hub, then get hub again with pie torch", &rules),
+            "push to GitHub, then GitHub again with PyTorch"
+        );
+    }
+
+    #[test]
+    fn regex_rules_support_flags_and_captures() {
+        let rules = rules(&[
+            (
+                r"(?i)\bteh\b",
+                Replacement::Rule {
+                    with: "the".into(),
+                    regex: true,
+                },
+            ),
+            (
+                r"version (\d+)",
+                Replacement::Rule {
+                    with: "v$1".into(),
+                    regex: true,
+                },
+            ),
+        ]);
+        assert_eq!(
+            apply_replacements("Teh fix is in version 2, not tehran", &rules),
+            "the fix is in v2, not tehran"
+        );
+    }
+
+    #[test]
+    fn rule_without_regex_flag_is_literal() {
+        let rules = rules(&[(
+            "a.c",
+            Replacement::Rule {
+                with: "x".into(),
+                regex: false,
+            },
+        )]);
+        assert_eq!(apply_replacements("abc a.c", &rules), "abc x");
+    }
+
+    #[test]
+    fn rules_run_in_key_order_on_each_others_output() {
+        let rules = rules(&[
+            ("b", Replacement::Literal("c".into())),
+            ("a", Replacement::Literal("b".into())),
+        ]);
+        assert_eq!(apply_replacements("a", &rules), "c");
+    }
+
+    #[test]
+    fn compile_rules_rejects_invalid_regex() {
+        let map = BTreeMap::from([(
+            "(unclosed".to_string(),
+            Replacement::Rule {
+                with: String::new(),
+                regex: true,
+            },
+        )]);
+        let err = compile_rules(&map).expect_err("invalid regex should fail");
+        match err {
+            WhsprError::Config(msg) => assert!(msg.contains("(unclosed"), "unexpected: {msg}"),
+            other => panic!("unexpected error variant: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Paths of per-user runtime files, shared by whspr-rs and whspr-osd.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Path of a runtime file such as `whspr-rs.pid`. `$XDG_RUNTIME_DIR` is
+/// already private to the user; the `/tmp` fallback is shared, so there the
+/// uid goes into the name to keep users apart.
+pub fn runtime_file(name: &str) -> PathBuf {
+    let uid = unsafe { libc::getuid() };
+    runtime_file_in(std::env::var_os("XDG_RUNTIME_DIR"), uid, name)
+}
+
+fn runtime_file_in(runtime_dir: Option<OsString>, uid: u32, name: &str) -> PathBuf {
+    match runtime_dir.filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join(name),
+        None => {
+            let name = match name.rsplit_once('.') {
+                Some((stem, ext)) => format!("{stem}-{uid}.{ext}"),
+                None => format!("{name}-{uid}"),
+            };
+            PathBuf::from("/tmp").join(name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_file_uses_runtime_dir_as_is() {
+        assert_eq!(
+            runtime_file_in(Some("/run/user/1000".into()), 1000, "whspr-rs.pid"),
+            PathBuf::from("/run/user/1000/whspr-rs.pid")
+        );
+    }
+
+    #[test]
+    fn runtime_file_puts_uid_in_tmp_fallback() {
+        assert_eq!(
+            runtime_file_in(None, 1000, "whspr-rs.pid"),
+            PathBuf::from("/tmp/whspr-rs-1000.pid")
+        );
+        assert_eq!(
+            runtime_file_in(Some("".into()), 42, "whspr-osd.text"),
+            PathBuf::from("/tmp/whspr-osd-42.text")
+        );
+    }
+}
@@ -2,8 +2,9 @@ use std::path::Path;
 
 use dialoguer::Select;
 
-use crate::config::{self, resolve_config_path};
-use crate::error::Result;
+use whspr_rs::config::{self, resolve_config_path};
+use whspr_rs::error::Result;
+
 use crate::model::{self, MODELS};
 
 pub async fn run_setup(config_path_override: Option<&Path>) -> Result<()> {
@@ -21,14 +22,20 @@ pub async fn run_setup(config_path_override: Option<&Path>) -> Result<()> {
         .items(&items)
         .default(0) // large-v3-turbo
         .interact()
-        .map_err(|e| crate::error::WhsprError::Config(format!("selection cancelled: {e}")))?;
+        .map_err(|e| whspr_rs::error::WhsprError::Config(format!("selection cancelled: {e}")))?;
 
     let chosen = &MODELS[selection];
     println!();
     tracing::info!("setup selected model: {}", chosen.name);
 
     // Download the model
-    model::download_model(chosen.name).await?;
+    model::download_model(
+        chosen.name,
+        config_path_override,
+        true,
+        model::DEFAULT_DOWNLOAD_RETRIES,
+    )
+    .await?;
     println!();
 
     // Generate or update config
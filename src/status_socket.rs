@@ -0,0 +1,120 @@
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use whspr_rs::error::Result;
+use whspr_rs::runtime;
+
+pub fn socket_path() -> PathBuf {
+    runtime::runtime_file("whspr-rs.sock")
+}
+
+struct Shared {
+    state: &'static str,
+    clients: Vec<UnixStream>,
+}
+
+/// Streams state changes to connected clients, one line per change. A new
+/// client first receives the current state. On drop every client gets a
+/// final `idle` and the socket file is removed.
+pub struct StatusSocket {
+    path: PathBuf,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl StatusSocket {
+    pub fn bind(path: &Path) -> Result<Self> {
+        // The pid lock guarantees we're the only instance, so anything left
+        // at the path is from a run that didn't clean up.
+        let _ = std::fs::remove_file(path);
+        let listener = UnixListener::bind(path)?;
+        // Bind honours the umask; other users have no business watching.
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        let shared = Arc::new(Mutex::new(Shared {
+            state: "idle",
+            clients: Vec::new(),
+        }));
+
+        // The accept loop lives as long as the process; this is a one-shot
+        // process, so there's no need to stop it early.
+        let accept_shared = Arc::clone(&shared);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                // A client that stops reading is dropped instead of stalling us.
+                if stream.set_nonblocking(true).is_err() {
+                    continue;
+                }
+                let Ok(mut shared) = accept_shared.lock() else {
+                    return;
+                };
+                if writeln!(stream, "{}", shared.state).is_ok() {
+                    shared.clients.push(stream);
+                }
+            }
+        });
+
+        tracing::debug!("status socket listening on {}", path.display());
+        Ok(Self {
+            path: path.to_path_buf(),
+            shared,
+        })
+    }
+
+    pub fn publish(&self, state: &'static str) {
+        let Ok(mut shared) = self.shared.lock() else {
+            return;
+        };
+        shared.state = state;
+        shared
+            .clients
+            .retain_mut(|client| writeln!(client, "{state}").is_ok());
+    }
+}
+
+impl Drop for StatusSocket {
+    fn drop(&mut self) {
+        self.publish("idle");
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.clients.clear();
+        }
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn clients_get_current_state_then_changes() {
+        let path = crate::test_support::unique_temp_path("status", "sock");
+        let socket = StatusSocket::bind(&path).unwrap();
+        socket.publish("recording");
+
+        let mut client = BufReader::new(UnixStream::connect(&path).unwrap());
+        let mut line = String::new();
+        client.read_line(&mut line).unwrap();
+        assert_eq!(line, "recording\n");
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        socket.publish("transcribing");
+        line.clear();
+        client.read_line(&mut line).unwrap();
+        assert_eq!(line, "transcribing\n");
+
+        drop(socket);
+        line.clear();
+        client.read_line(&mut line).unwrap();
+        assert_eq!(line, "idle\n");
+        line.clear();
+        assert_eq!(client.read_line(&mut line).unwrap(), 0);
+        assert!(!path.exists());
+    }
+}
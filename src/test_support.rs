@@ -1,3 +1,6 @@
+//! Shared by the library and binary tests, which each use only part of it.
+#![allow(dead_code)]
+
 use std::path::PathBuf;
 use std::sync::{Mutex, MutexGuard, OnceLock};
 
@@ -1,8 +1,11 @@
 use std::path::Path;
 
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+use serde::Serialize;
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperSegment,
+};
 
-use crate::config::WhisperConfig;
+use crate::config::{Sampling, WhisperConfig};
 use crate::error::{Result, WhsprError};
 
 pub trait TranscriptionBackend: Send + Sync {
@@ -12,6 +15,22 @@ pub trait TranscriptionBackend: Send + Sync {
 pub struct WhisperLocal {
     ctx: WhisperContext,
     language: String,
+    initial_prompt: String,
+    translate: bool,
+    sampling: Sampling,
+    beam_size: u32,
+    n_threads: u32,
+    chunk_secs: f64,
+    overlap_secs: f64,
+    parallel_chunks: u32,
+    word_timestamps: bool,
+    no_speech_threshold: f32,
+    no_context: bool,
+    single_segment: bool,
+    temperature: f32,
+    temperature_inc: f32,
+    entropy_threshold: f32,
+    logprob_threshold: f32,
 }
 
 impl WhisperLocal {
@@ -23,16 +42,9 @@ impl WhisperLocal {
             )));
         }
 
-        tracing::info!("loading whisper model from {}", model_path.display());
+        check_ggml_magic(model_path)?;
 
-        let mut ctx_params = WhisperContextParameters::default();
-        ctx_params.use_gpu(config.use_gpu);
-        ctx_params.flash_attn(config.use_gpu && config.flash_attn);
-        tracing::info!(
-            "whisper acceleration: use_gpu={}, flash_attn={}",
-            config.use_gpu,
-            config.use_gpu && config.flash_attn
-        );
+        tracing::info!("loading whisper model from {}", model_path.display());
 
         let model_path_str = model_path.to_str().ok_or_else(|| {
             WhsprError::Transcription(format!(
@@ -41,39 +53,333 @@ impl WhisperLocal {
             ))
         })?;
 
-        let ctx = WhisperContext::new_with_params(model_path_str, ctx_params)
-            .map_err(|e| {
-                if config.use_gpu {
-                    WhsprError::Transcription(format!(
-                        "failed to load whisper model with GPU enabled: {e}. Set [whisper].use_gpu = false to force CPU."
-                    ))
-                } else {
+        let ctx = match load_context(model_path_str, config.use_gpu, config.flash_attn) {
+            Ok(ctx) => ctx,
+            Err(e) if config.use_gpu && config.gpu_fallback => {
+                // Usually out of GPU memory for the chosen model.
+                tracing::warn!(
+                    "failed to load whisper model on the GPU ({e}), falling back to CPU; \
+                     transcription will be slower. Use a smaller model, or set \
+                     [whisper].gpu_fallback = false to fail instead"
+                );
+                load_context(model_path_str, false, false).map_err(|e| {
                     WhsprError::Transcription(format!("failed to load whisper model: {e}"))
-                }
-            })?;
+                })?
+            }
+            Err(e) if config.use_gpu => {
+                return Err(WhsprError::Transcription(format!(
+                    "failed to load whisper model with GPU enabled: {e}. Set [whisper].use_gpu = false to force CPU."
+                )));
+            }
+            Err(e) => {
+                return Err(WhsprError::Transcription(format!(
+                    "failed to load whisper model: {e}"
+                )));
+            }
+        };
 
         tracing::info!("whisper model loaded successfully");
 
         Ok(Self {
             ctx,
             language: config.language.clone(),
+            initial_prompt: config.initial_prompt.clone(),
+            translate: config.translate,
+            sampling: config.sampling,
+            beam_size: config.beam_size,
+            n_threads: config.n_threads,
+            chunk_secs: config.chunk_secs,
+            overlap_secs: config.overlap_secs,
+            parallel_chunks: config.parallel_chunks,
+            word_timestamps: config.word_timestamps,
+            no_speech_threshold: config.no_speech_threshold,
+            no_context: config.no_context,
+            single_segment: config.single_segment,
+            temperature: config.temperature,
+            temperature_inc: config.temperature_inc,
+            entropy_threshold: config.entropy_threshold,
+            logprob_threshold: config.logprob_threshold,
         })
     }
 }
 
-const CHUNK_DURATION_SECS: f64 = 30.0;
-const OVERLAP_SECS: f64 = 1.0;
+fn load_context(
+    model_path: &str,
+    use_gpu: bool,
+    flash_attn: bool,
+) -> std::result::Result<WhisperContext, whisper_rs::WhisperError> {
+    // Flash attention only applies to the GPU path, so force it off on CPU.
+    let flash_attn = use_gpu && flash_attn;
+    let mut ctx_params = WhisperContextParameters::default();
+    ctx_params.use_gpu(use_gpu);
+    ctx_params.flash_attn(flash_attn);
+    if use_gpu {
+        tracing::info!("GPU acceleration enabled (flash_attn={flash_attn})");
+    } else {
+        tracing::info!("GPU acceleration disabled, running whisper on CPU");
+    }
+    WhisperContext::new_with_params(model_path, ctx_params)
+}
+
+/// `GGML_FILE_MAGIC` (0x67676d6c) as whisper.cpp reads it: a little-endian u32.
+const GGML_MAGIC: [u8; 4] = 0x6767_6d6c_u32.to_le_bytes();
+
+/// Fail early with an actionable message if the file isn't a ggml model, e.g.
+/// a truncated download or an HTML error page saved under the model name.
+pub fn check_ggml_magic(model_path: &Path) -> Result<()> {
+    use std::io::Read;
+
+    let mut magic = [0u8; 4];
+    let read = std::fs::File::open(model_path).and_then(|mut f| f.read_exact(&mut magic));
+    if read.is_err() || magic != GGML_MAGIC {
+        return Err(WhsprError::Transcription(format!(
+            "{} is not a valid ggml whisper model. It may be incomplete or the wrong file; re-download it with `whspr-rs model download <name>`.",
+            model_path.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Language codes whisper understands, in whisper.cpp's order.
+#[rustfmt::skip]
+pub const LANGUAGES: &[&str] = &[
+    "en", "zh", "de", "es", "ru", "ko", "fr", "ja", "pt", "tr", "pl", "ca", "nl", "ar", "sv",
+    "it", "id", "hi", "fi", "vi", "he", "uk", "el", "ms", "cs", "ro", "da", "hu", "ta", "no",
+    "th", "ur", "hr", "bg", "lt", "la", "mi", "ml", "cy", "sk", "te", "fa", "lv", "bn", "sr",
+    "az", "sl", "kn", "et", "mk", "br", "eu", "is", "hy", "ne", "mn", "bs", "kk", "sq", "sw",
+    "gl", "mr", "pa", "si", "km", "sn", "yo", "so", "af", "oc", "ka", "be", "tg", "sd", "gu",
+    "am", "yi", "lo", "uz", "fo", "ht", "ps", "tk", "nn", "mt", "sa", "lb", "my", "bo", "tl",
+    "mg", "as", "tt", "haw", "ln", "ha", "ba", "jw", "su", "yue",
+];
+
+/// Accepts "auto" or one of `LANGUAGES`.
+pub fn check_language(language: &str) -> Result<()> {
+    if language == "auto" || LANGUAGES.contains(&language) {
+        return Ok(());
+    }
+    Err(WhsprError::Config(format!(
+        "unsupported language '{language}'; use \"auto\" or one of: {}",
+        LANGUAGES.join(", ")
+    )))
+}
 
 /// Minimum RMS energy to consider audio as containing speech (~-40 dBFS).
 const MIN_RMS_THRESHOLD: f32 = 0.01;
 /// Minimum duration in seconds for meaningful speech input.
 const MIN_DURATION_SECS: f64 = 0.3;
+/// Below this detection probability the auto-detected language is likely wrong.
+const MIN_LANGUAGE_CONFIDENCE: f32 = 0.5;
+
+/// The probability of `lang_id` in whisper's language probabilities, if it
+/// is too low to trust the detection.
+fn low_language_confidence(probs: &[f32], lang_id: i32) -> Option<f32> {
+    let confidence = usize::try_from(lang_id)
+        .ok()
+        .and_then(|id| probs.get(id).copied())
+        .unwrap_or(0.0);
+    (confidence < MIN_LANGUAGE_CONFIDENCE).then_some(confidence)
+}
 
 impl TranscriptionBackend for WhisperLocal {
     fn transcribe(&self, audio: &[f32], sample_rate: u32) -> Result<String> {
+        let transcript = self.transcribe_detailed(audio, sample_rate)?;
+        let text = segments_to_text(&transcript.segments);
+        if !text.is_empty() {
+            tracing::info!("transcription result: {text:?}");
+        }
+        Ok(text)
+    }
+}
+
+/// A transcribed segment with timestamps in seconds from the start of the audio.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Segment {
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+    pub no_speech_prob: f32,
+    /// Per-word timing, only filled in when `word_timestamps` is enabled.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<Word>,
+}
+
+/// A word with timestamps in seconds from the start of the audio.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Word {
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+    /// Mean probability of the word's tokens.
+    pub probability: f32,
+}
+
+/// Full transcription result: the segments plus the language they were
+/// decoded in (the detected language when configured as "auto").
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Transcript {
+    pub language: String,
+    pub segments: Vec<Segment>,
+}
+
+/// Join segment text into a single transcript.
+///
+/// Whisper emits segment text with its own leading whitespace, so segments are
+/// concatenated as-is rather than joined with a separator.
+pub fn segments_to_text(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Remove segments whisper rates as likely silence, returning how many were
+/// dropped. Whisper often decodes a confident-looking phrase from silence, but
+/// its no-speech probability for that segment stays high.
+fn drop_no_speech_segments(segments: &mut Vec<Segment>, threshold: f32) -> usize {
+    let before = segments.len();
+    segments.retain(|s| {
+        let keep = s.no_speech_prob <= threshold;
+        if !keep {
+            tracing::debug!(
+                "dropping segment {:?} (no-speech p={:.2})",
+                s.text,
+                s.no_speech_prob
+            );
+        }
+        keep
+    });
+    before - segments.len()
+}
+
+/// Longest run of words checked when stitching text across a chunk boundary.
+const MAX_OVERLAP_WORDS: usize = 5;
+
+/// Append a chunk's segments, dropping any already covered by earlier chunks.
+///
+/// Adjacent chunks overlap by `overlap_secs`, so a segment straddling the
+/// boundary is emitted by both. A segment is kept only if its midpoint lies
+/// past the end of the last segment already collected. Segment boundaries
+/// rarely line up across chunks, so words the new chunk repeats from the tail
+/// of the previous one are trimmed from its first kept segment as well.
+fn merge_chunk_segments(segments: &mut Vec<Segment>, chunk: Vec<Segment>) {
+    let covered_until = segments.last().map(|s| s.end).unwrap_or(0.0);
+    let mut kept: Vec<Segment> = chunk
+        .into_iter()
+        .filter(|s| (s.start + s.end) / 2.0 >= covered_until)
+        .collect();
+
+    if let (Some(prev), Some(first)) = (segments.last(), kept.first_mut()) {
+        let repeated = repeated_word_count(&prev.text, &first.text);
+        first.text = drop_leading_words(&first.text, repeated).to_string();
+        first.words.drain(..repeated.min(first.words.len()));
+    }
+    kept.retain(|s| !s.text.trim().is_empty());
+    segments.extend(kept);
+}
+
+/// Number of leading words of `next` that repeat the trailing words of `prev`,
+/// compared case-insensitively and ignoring punctuation.
+fn repeated_word_count(prev: &str, next: &str) -> usize {
+    let prev: Vec<String> = prev.split_whitespace().map(normalize_word).collect();
+    let next: Vec<String> = next.split_whitespace().map(normalize_word).collect();
+    let max = MAX_OVERLAP_WORDS.min(prev.len()).min(next.len());
+    (1..=max)
+        .rev()
+        .find(|&n| prev[prev.len() - n..] == next[..n])
+        .unwrap_or(0)
+}
+
+fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// A decoded token: raw text bytes, start and end in seconds, and probability.
+struct TimedToken {
+    bytes: Vec<u8>,
+    start: f64,
+    end: f64,
+    probability: f32,
+}
+
+/// Group tokens into words. Whisper's BPE tokens carry the leading space of
+/// the word they start, so a token beginning with whitespace opens a new word
+/// and any other token continues the current one. A word's bytes are decoded
+/// only once it is complete, since BPE can split a multi-byte character
+/// across tokens.
+fn tokens_to_words(tokens: Vec<TimedToken>) -> Vec<Word> {
+    let mut words: Vec<(Vec<u8>, Word, usize)> = Vec::new();
+    for token in tokens {
+        let continues = !token.bytes.first().is_some_and(u8::is_ascii_whitespace);
+        match words.last_mut() {
+            Some((bytes, word, count)) if continues => {
+                bytes.extend_from_slice(&token.bytes);
+                word.end = token.end;
+                word.probability += token.probability;
+                *count += 1;
+            }
+            _ => words.push((
+                token.bytes,
+                Word {
+                    text: String::new(),
+                    start: token.start,
+                    end: token.end,
+                    probability: token.probability,
+                },
+                1,
+            )),
+        }
+    }
+    words
+        .into_iter()
+        .filter_map(|(bytes, mut word, count)| {
+            word.text = String::from_utf8_lossy(&bytes).trim().to_string();
+            word.probability /= count as f32;
+            (!word.text.is_empty()).then_some(word)
+        })
+        .collect()
+}
+
+/// Drop the first `n` words, keeping the whitespace before the next one so
+/// whisper's leading-space convention survives.
+fn drop_leading_words(text: &str, n: usize) -> &str {
+    let mut rest = text;
+    for _ in 0..n {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        rest = &rest[end..];
+    }
+    rest
+}
+
+impl WhisperLocal {
+    /// Transcribe audio into timestamped segments.
+    ///
+    /// Long audio is split into overlapping chunks; segment timestamps are
+    /// shifted by the chunk offset so they are absolute for the whole input.
+    pub fn transcribe_detailed(&self, audio: &[f32], sample_rate: u32) -> Result<Transcript> {
+        self.transcribe_with_progress(audio, sample_rate, |_, _| {})
+    }
+
+    /// Like [`Self::transcribe_detailed`], calling `progress(done, total)` as
+    /// each chunk of long audio finishes. Short audio reports no progress.
+    pub fn transcribe_with_progress(
+        &self,
+        audio: &[f32],
+        sample_rate: u32,
+        progress: impl Fn(usize, usize),
+    ) -> Result<Transcript> {
+        let empty = Transcript {
+            language: self.language.clone(),
+            segments: Vec::new(),
+        };
         if audio.is_empty() || sample_rate == 0 {
             tracing::info!("empty audio or zero sample rate, skipping");
-            return Ok(String::new());
+            return Ok(empty);
         }
 
         // Audio diagnostics
@@ -93,7 +399,7 @@ impl TranscriptionBackend for WhisperLocal {
                 duration_secs,
                 MIN_DURATION_SECS
             );
-            return Ok(String::new());
+            return Ok(empty);
         }
         if rms < MIN_RMS_THRESHOLD {
             tracing::info!(
@@ -101,67 +407,149 @@ impl TranscriptionBackend for WhisperLocal {
                 rms,
                 MIN_RMS_THRESHOLD
             );
-            return Ok(String::new());
+            return Ok(empty);
         }
 
-        let chunk_size = (CHUNK_DURATION_SECS * sample_rate as f64) as usize;
-        let overlap = (OVERLAP_SECS * sample_rate as f64) as usize;
+        let chunk_size = (self.chunk_secs * sample_rate as f64) as usize;
+        let overlap = (self.overlap_secs * sample_rate as f64) as usize;
 
         if audio.len() <= chunk_size {
             // Short audio: process directly
-            self.transcribe_chunk(audio)
-        } else {
-            // Long audio: split into overlapping chunks
-            let mut results = Vec::new();
-            let mut offset = 0;
-
-            while offset < audio.len() {
-                let end = (offset + chunk_size).min(audio.len());
-                let chunk = &audio[offset..end];
-                tracing::info!(
-                    "processing chunk: {:.1}s - {:.1}s",
-                    offset as f64 / sample_rate as f64,
-                    end as f64 / sample_rate as f64
-                );
+            return self.transcribe_chunk(audio, 0.0, self.thread_budget());
+        }
 
-                let text = self.transcribe_chunk(chunk)?;
-                if !text.is_empty() {
-                    results.push(text);
-                }
+        // Long audio: split into overlapping chunks
+        let mut chunks = Vec::new();
+        let mut offset = 0;
+        while offset < audio.len() {
+            let end = (offset + chunk_size).min(audio.len());
+            chunks.push(offset..end);
+            if end == audio.len() {
+                break;
+            }
+            offset = end - overlap;
+        }
+
+        // Each parallel chunk gets its own whisper state (and its own share of
+        // the thread budget). States are large, so only one batch is alive at
+        // a time and they are dropped before the next batch starts.
+        let parallel = (self.parallel_chunks.max(1) as usize).min(chunks.len());
+        let threads_per_state = (self.thread_budget() / parallel as i32).max(1);
+        if parallel > 1 {
+            tracing::info!(
+                "transcribing {} chunks, {parallel} at a time with {threads_per_state} threads each",
+                chunks.len()
+            );
+        }
+
+        let mut transcript = empty;
+        let mut language = None;
+        let mut done = 0;
+        progress(done, chunks.len());
+
+        for batch in chunks.chunks(parallel) {
+            let results: Vec<Result<Transcript>> = std::thread::scope(|scope| {
+                let handles: Vec<_> = batch
+                    .iter()
+                    .map(|range| {
+                        let offset_secs = range.start as f64 / sample_rate as f64;
+                        tracing::info!(
+                            "processing chunk: {:.1}s - {:.1}s",
+                            offset_secs,
+                            range.end as f64 / sample_rate as f64
+                        );
+                        let chunk = &audio[range.clone()];
+                        scope.spawn(move || {
+                            self.transcribe_chunk(chunk, offset_secs, threads_per_state)
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| {
+                        handle.join().unwrap_or_else(|_| {
+                            Err(WhsprError::Transcription(
+                                "chunk transcription thread panicked".into(),
+                            ))
+                        })
+                    })
+                    .collect()
+            });
 
-                if end == audio.len() {
-                    break;
+            // Results are in chunk order, so merging stays sequential.
+            for chunk_transcript in results {
+                let chunk_transcript = chunk_transcript?;
+                if language.is_none() && !chunk_transcript.segments.is_empty() {
+                    language = Some(chunk_transcript.language);
                 }
-                offset = end - overlap;
+                merge_chunk_segments(&mut transcript.segments, chunk_transcript.segments);
+                done += 1;
+                progress(done, chunks.len());
             }
+        }
 
-            let text = results.join(" ");
-            tracing::info!("transcription result: {text:?}");
-            Ok(text)
+        if let Some(language) = language {
+            transcript.language = language;
         }
+        Ok(transcript)
     }
-}
 
-impl WhisperLocal {
-    fn transcribe_chunk(&self, audio: &[f32]) -> Result<String> {
-        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    /// Run whisper on 16 kHz `audio` as a single chunk, without the length
+    /// and silence gates, so even a quiet test clip reaches the model.
+    pub fn transcribe_ungated(&self, audio: &[f32]) -> Result<Transcript> {
+        self.transcribe_chunk(audio, 0.0, self.thread_budget())
+    }
+
+    /// Total CPU threads whisper may use: the configured count, or all cores.
+    fn thread_budget(&self) -> i32 {
+        if self.n_threads > 0 {
+            self.n_threads as i32
+        } else {
+            std::thread::available_parallelism()
+                .map(|n| n.get() as i32)
+                .unwrap_or(4)
+        }
+    }
+
+    fn transcribe_chunk(
+        &self,
+        audio: &[f32],
+        offset_secs: f64,
+        n_threads: i32,
+    ) -> Result<Transcript> {
+        let strategy = match self.sampling {
+            Sampling::Greedy => SamplingStrategy::Greedy { best_of: 1 },
+            Sampling::Beam => SamplingStrategy::BeamSearch {
+                beam_size: self.beam_size.max(1) as i32,
+                patience: -1.0,
+            },
+        };
+        let mut params = FullParams::new(strategy);
 
         if self.language == "auto" {
             params.set_language(None);
         } else {
             params.set_language(Some(&self.language));
         }
-        params.set_translate(false);
+        params.set_translate(self.translate);
+        // Applied to every chunk so long audio keeps the vocabulary bias.
+        if !self.initial_prompt.is_empty() {
+            params.set_initial_prompt(&self.initial_prompt);
+        }
         params.set_print_special(false);
         params.set_print_progress(false);
         params.set_print_realtime(false);
         params.set_print_timestamps(false);
         params.set_suppress_blank(true);
         params.set_suppress_nst(true);
-        let n_threads = std::thread::available_parallelism()
-            .map(|n| n.get() as i32)
-            .unwrap_or(4);
         params.set_n_threads(n_threads);
+        params.set_token_timestamps(self.word_timestamps);
+        params.set_no_context(self.no_context);
+        params.set_single_segment(self.single_segment);
+        params.set_temperature(self.temperature);
+        params.set_temperature_inc(self.temperature_inc);
+        params.set_entropy_thold(self.entropy_threshold);
+        params.set_logprob_thold(self.logprob_threshold);
 
         let mut state = self.ctx.create_state().map_err(|e| {
             WhsprError::Transcription(format!("failed to create whisper state: {e}"))
@@ -173,28 +561,293 @@ impl WhisperLocal {
 
         let num_segments = state.full_n_segments();
 
-        let mut text = String::new();
+        let mut segments = Vec::new();
         for i in 0..num_segments {
-            if let Some(segment) = state.get_segment(i) {
-                match segment.to_str() {
-                    Ok(s) => text.push_str(s),
-                    Err(_) => {
-                        if let Ok(lossy) = segment.to_str_lossy() {
+            let Some(segment) = state.get_segment(i) else {
+                continue;
+            };
+            let text = match segment.to_str() {
+                Ok(s) => s.to_string(),
+                Err(_) => match segment.to_str_lossy() {
+                    Ok(lossy) => {
+                        tracing::warn!(
+                            "segment {i} contains invalid UTF-8, using lossy conversion"
+                        );
+                        lossy.into_owned()
+                    }
+                    Err(_) => continue,
+                },
+            };
+            if text.trim().is_empty() {
+                continue;
+            }
+            let words = if self.word_timestamps {
+                self.segment_words(&segment, offset_secs)
+            } else {
+                Vec::new()
+            };
+            // Whisper reports segment timestamps in centiseconds.
+            segments.push(Segment {
+                text,
+                start: offset_secs + segment.start_timestamp() as f64 / 100.0,
+                end: offset_secs + segment.end_timestamp() as f64 / 100.0,
+                no_speech_prob: segment.no_speech_probability(),
+                words,
+            });
+        }
+
+        let dropped = drop_no_speech_segments(&mut segments, self.no_speech_threshold);
+        if dropped > 0 {
+            tracing::info!(
+                "dropped {dropped} segment(s) with no-speech probability above {}",
+                self.no_speech_threshold
+            );
+        }
+
+        let text = segments_to_text(&segments);
+        if !text.is_empty() {
+            tracing::debug!("chunk transcription: {text:?}");
+        }
+
+        let language = if self.language == "auto" {
+            let lang_id = state.full_lang_id_from_state();
+            let detected = whisper_rs::get_lang_str(lang_id).unwrap_or("auto");
+            tracing::info!("detected language: {detected}");
+            // whisper doesn't keep the probabilities from its own detection,
+            // so get them with one more encoder pass, on the first chunk only.
+            if offset_secs == 0.0 {
+                match state.lang_detect(0, n_threads.max(1) as usize) {
+                    Ok((_, probs)) => {
+                        if let Some(confidence) = low_language_confidence(&probs, lang_id) {
                             tracing::warn!(
-                                "segment {i} contains invalid UTF-8, using lossy conversion"
+                                "low confidence in detected language '{detected}' (p={confidence:.2}), transcription may be unreliable; set [whisper].language to force one"
                             );
-                            text.push_str(&lossy);
                         }
                     }
+                    Err(e) => tracing::debug!("language probabilities unavailable: {e}"),
                 }
             }
+            detected.to_string()
+        } else {
+            self.language.clone()
+        };
+        if self.translate {
+            tracing::info!("translating from '{language}' to English");
         }
 
-        let text = text.trim().to_string();
-        if !text.is_empty() {
-            tracing::debug!("chunk transcription: {text:?}");
+        Ok(Transcript { language, segments })
+    }
+
+    /// Word timing for a segment, built from its token timestamps. Needs
+    /// `set_token_timestamps` on the params the segment was decoded with.
+    fn segment_words(&self, segment: &WhisperSegment<'_>, offset_secs: f64) -> Vec<Word> {
+        let eot = self.ctx.token_eot();
+        let tokens = (0..segment.n_tokens())
+            .filter_map(|i| segment.get_token(i))
+            .filter_map(|token| {
+                let data = token.token_data();
+                // Timestamp and control tokens sit at and above end-of-text.
+                if data.id >= eot {
+                    return None;
+                }
+                let bytes = token.to_bytes().ok()?.to_vec();
+                Some(TimedToken {
+                    bytes,
+                    start: offset_secs + data.t0 as f64 / 100.0,
+                    end: offset_secs + data.t1 as f64 / 100.0,
+                    probability: data.p,
+                })
+            })
+            .collect();
+        tokens_to_words(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, start: f64, end: f64) -> Segment {
+        Segment {
+            text: text.to_string(),
+            start,
+            end,
+            no_speech_prob: 0.0,
+            words: Vec::new(),
         }
+    }
 
-        Ok(text)
+    #[test]
+    fn check_language_accepts_auto_and_known_codes() {
+        assert!(check_language("auto").is_ok());
+        assert!(check_language("en").is_ok());
+        assert!(check_language("yue").is_ok());
+        assert!(check_language("eng").is_err());
+        assert!(check_language("").is_err());
+    }
+
+    #[test]
+    fn low_language_confidence_flags_only_unsure_detections() {
+        let probs = [0.1, 0.85, 0.05];
+        assert_eq!(low_language_confidence(&probs, 1), None);
+        assert_eq!(low_language_confidence(&probs, 0), Some(0.1));
+        assert_eq!(low_language_confidence(&probs, 7), Some(0.0));
+        assert_eq!(low_language_confidence(&probs, -1), Some(0.0));
+    }
+
+    #[test]
+    fn check_ggml_magic_accepts_ggml_header() {
+        let path = crate::test_support::unique_temp_path("model-valid", "bin");
+        std::fs::write(&path, [GGML_MAGIC.as_slice(), &[0u8; 8]].concat()).expect("write model");
+        assert!(check_ggml_magic(&path).is_ok());
+    }
+
+    #[test]
+    fn check_ggml_magic_rejects_other_files() {
+        let html = crate::test_support::unique_temp_path("model-html", "bin");
+        std::fs::write(&html, "<!DOCTYPE html>").expect("write html");
+        let short = crate::test_support::unique_temp_path("model-short", "bin");
+        std::fs::write(&short, "gg").expect("write short file");
+
+        for path in [html, short] {
+            match check_ggml_magic(&path).expect_err("should reject") {
+                WhsprError::Transcription(msg) => {
+                    assert!(msg.contains("not a valid ggml"), "unexpected: {msg}");
+                }
+                other => panic!("unexpected error variant: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn merge_chunk_segments_drops_overlap_duplicates() {
+        let mut segments = vec![
+            segment(" First.", 0.0, 25.0),
+            segment(" Boundary.", 25.0, 29.8),
+        ];
+        let next_chunk = vec![
+            segment(" Boundary.", 29.0, 29.8),
+            segment(" Second.", 29.8, 35.0),
+        ];
+        merge_chunk_segments(&mut segments, next_chunk);
+        let texts: Vec<&str> = segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec![" First.", " Boundary.", " Second."]);
+    }
+
+    #[test]
+    fn merge_chunk_segments_trims_repeated_words_at_boundary() {
+        let tail = " one two three four five";
+        for repeated in 1..=5 {
+            let head: Vec<&str> = tail.split_whitespace().skip(5 - repeated).collect();
+            let next_text = format!(" {} six seven.", head.join(" "));
+            let mut segments = vec![segment(tail, 0.0, 29.5)];
+            merge_chunk_segments(&mut segments, vec![segment(&next_text, 29.5, 33.0)]);
+            assert_eq!(
+                segments_to_text(&segments),
+                "one two three four five six seven.",
+                "failed with {repeated} repeated words"
+            );
+        }
+    }
+
+    #[test]
+    fn merge_chunk_segments_ignores_case_and_punctuation_in_overlap() {
+        let mut segments = vec![segment(" We shipped it, Friday.", 0.0, 29.5)];
+        merge_chunk_segments(
+            &mut segments,
+            vec![segment(" friday. Then we rested.", 29.5, 33.0)],
+        );
+        assert_eq!(
+            segments_to_text(&segments),
+            "We shipped it, Friday. Then we rested."
+        );
+    }
+
+    #[test]
+    fn merge_chunk_segments_keeps_text_without_overlap() {
+        let mut segments = vec![segment(" Hello there.", 0.0, 29.5)];
+        merge_chunk_segments(&mut segments, vec![segment(" General Kenobi.", 29.5, 33.0)]);
+        assert_eq!(segments_to_text(&segments), "Hello there. General Kenobi.");
+    }
+
+    #[test]
+    fn merge_chunk_segments_drops_segment_that_is_all_overlap() {
+        let mut segments = vec![segment(" Stop here.", 0.0, 29.5)];
+        merge_chunk_segments(&mut segments, vec![segment(" here.", 29.5, 30.0)]);
+        assert_eq!(segments.len(), 1);
+    }
+
+    #[test]
+    fn merge_chunk_segments_keeps_everything_for_first_chunk() {
+        let mut segments = Vec::new();
+        merge_chunk_segments(&mut segments, vec![segment(" Hi.", 0.0, 1.0)]);
+        assert_eq!(segments.len(), 1);
+    }
+
+    #[test]
+    fn drop_no_speech_segments_uses_threshold() {
+        let mut silence = segment(" Thanks for watching!", 2.0, 3.0);
+        silence.no_speech_prob = 0.9;
+        let mut segments = vec![segment(" Hello.", 0.0, 1.0), silence];
+
+        assert_eq!(drop_no_speech_segments(&mut segments, 1.0), 0);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(drop_no_speech_segments(&mut segments, 0.6), 1);
+        assert_eq!(segments_to_text(&segments), "Hello.");
+    }
+
+    fn token(text: &str, start: f64, end: f64, probability: f32) -> TimedToken {
+        raw_token(text.as_bytes(), start, end, probability)
+    }
+
+    fn raw_token(bytes: &[u8], start: f64, end: f64, probability: f32) -> TimedToken {
+        TimedToken {
+            bytes: bytes.to_vec(),
+            start,
+            end,
+            probability,
+        }
+    }
+
+    #[test]
+    fn tokens_to_words_joins_subword_tokens() {
+        let words = tokens_to_words(vec![
+            token(" Kub", 0.0, 0.2, 0.8),
+            token("ern", 0.2, 0.4, 0.6),
+            token("etes", 0.4, 0.6, 1.0),
+            token(" rocks", 0.7, 1.0, 0.9),
+            token(".", 1.0, 1.1, 0.5),
+        ]);
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].text, "Kubernetes");
+        assert_eq!((words[0].start, words[0].end), (0.0, 0.6));
+        assert!((words[0].probability - 0.8).abs() < 1e-6);
+        assert_eq!(words[1].text, "rocks.");
+        assert_eq!((words[1].start, words[1].end), (0.7, 1.1));
+    }
+
+    #[test]
+    fn tokens_to_words_decodes_characters_split_across_tokens() {
+        // "é" is 0xC3 0xA9; BPE may put each byte in its own token.
+        let words = tokens_to_words(vec![
+            raw_token(b" caf\xC3", 0.0, 0.2, 1.0),
+            raw_token(b"\xA9", 0.2, 0.3, 1.0),
+            raw_token(" \u{4F60}".as_bytes(), 0.4, 0.5, 1.0),
+        ]);
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, vec!["café", "\u{4F60}"]);
+    }
+
+    #[test]
+    fn merge_chunk_segments_drops_words_of_trimmed_overlap() {
+        let mut next = segment(" five six.", 29.5, 31.0);
+        next.words = tokens_to_words(vec![
+            token(" five", 29.5, 30.0, 1.0),
+            token(" six.", 30.0, 31.0, 1.0),
+        ]);
+        let mut segments = vec![segment(" four five", 0.0, 29.8)];
+        merge_chunk_segments(&mut segments, vec![next]);
+        assert_eq!(segments[1].words.len(), 1);
+        assert_eq!(segments[1].words[0].text, "six.");
     }
 }
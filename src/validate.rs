@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use whspr_rs::audio;
+use whspr_rs::config::{self, Config};
+use whspr_rs::error::{Result, WhsprError};
+use whspr_rs::keys;
+use whspr_rs::transcribe;
+
+/// Run every check and print a pass/fail line for each, so one bad setting
+/// doesn't hide the next.
+pub fn validate_config(config_path: Option<&Path>) -> Result<()> {
+    let path = config::resolve_config_path(config_path);
+    let mut config = match Config::load(config_path) {
+        Ok(config) => {
+            report(&format!("config {}", path.display()), Ok(()));
+            config
+        }
+        Err(e) => {
+            report(&format!("config {}", path.display()), Err(e));
+            return Err(WhsprError::Config("config is invalid".into()));
+        }
+    };
+
+    if let Err(e) = config.apply_env_overrides() {
+        report("environment overrides", Err(e));
+        return Err(WhsprError::Config("config is invalid".into()));
+    }
+
+    let model_path = config.resolved_model_path();
+    let checks = [
+        (
+            format!("no unknown keys in {}", path.display()),
+            check_unknown_keys(&path),
+        ),
+        (
+            format!("model {}", model_path.display()),
+            check_model(&model_path),
+        ),
+        (
+            format!("language \"{}\"", config.whisper.language),
+            transcribe::check_language(&config.whisper.language),
+        ),
+        (
+            format!("paste keys {}", config.inject.paste_keys.join("+")),
+            keys::parse_key_combo(&config.inject.paste_keys).map(|_| ()),
+        ),
+        (
+            audio_device_label(&config.audio.device),
+            audio::find_input_device(&config.audio.device).map(|_| ()),
+        ),
+    ];
+
+    let mut failed = 0;
+    for (label, result) in checks {
+        if result.is_err() {
+            failed += 1;
+        }
+        report(&label, result);
+    }
+
+    if failed > 0 {
+        return Err(WhsprError::Config(format!("{failed} check(s) failed")));
+    }
+    println!("config is valid");
+    Ok(())
+}
+
+fn report(label: &str, result: Result<()>) {
+    match result {
+        Ok(()) => println!("  ok    {label}"),
+        Err(e) => println!("  FAIL  {label}: {e}"),
+    }
+}
+
+fn audio_device_label(device: &str) -> String {
+    if device.is_empty() {
+        "audio device (system default)".into()
+    } else {
+        format!("audio device \"{device}\"")
+    }
+}
+
+fn check_unknown_keys(path: &Path) -> Result<()> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        // A missing file means defaults, which is fine.
+        Err(_) => return Ok(()),
+    };
+    let unknown = config::unknown_keys(&contents);
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(WhsprError::Config(format!(
+            "not recognized: {}",
+            unknown.join(", ")
+        )))
+    }
+}
+
+pub fn check_model(model_path: &Path) -> Result<()> {
+    if !model_path.exists() {
+        return Err(WhsprError::Config(
+            "file not found (see `whspr-rs model list`)".into(),
+        ));
+    }
+    transcribe::check_ggml_magic(model_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_model_rejects_missing_and_non_ggml_files() {
+        assert!(check_model(Path::new("/nonexistent/ggml-tiny.bin")).is_err());
+
+        let path = crate::test_support::unique_temp_path("validate-model", "bin");
+        std::fs::write(&path, b"<html>not found</html>").unwrap();
+        assert!(check_model(&path).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+}